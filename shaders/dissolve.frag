@@ -0,0 +1,21 @@
+#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform sampler2D Texture;
+uniform float Progress;
+uniform vec2 Resolution;
+
+void main() {
+    // Every block of 6x6 pixels gets a pseudo random threshold.
+    vec2 block = floor(uv * Resolution / 6.0);
+    float noise = fract(sin(dot(block, vec2(12.9898, 78.233))) * 43758.5453);
+
+    if (noise < Progress) {
+        discard;
+    }
+
+    gl_FragColor = texture2D(Texture, uv) * color;
+}
@@ -0,0 +1,21 @@
+#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform sampler2D Texture;
+uniform float Progress;
+uniform vec2 Resolution;
+
+void main() {
+    vec2 position = uv * Resolution;
+    vec2 center = Resolution / 2.0;
+
+    // The circle grows until it covers the corners of the screen.
+    if (distance(position, center) < Progress * length(center)) {
+        discard;
+    }
+
+    gl_FragColor = texture2D(Texture, uv) * color;
+}
@@ -0,0 +1,15 @@
+#version 100
+precision lowp float;
+
+varying vec2 uv;
+varying vec2 uv_screen;
+varying vec2 center;
+
+uniform sampler2D _ScreenTexture;
+
+void main() {
+    float gradient = length(uv);
+    vec2 uv_zoom = (uv_screen - center) * gradient + center;
+
+    gl_FragColor = texture2D(_ScreenTexture, uv_zoom);
+}
@@ -0,0 +1,377 @@
+use std::{
+    f32::consts::{PI, TAU},
+    time::Duration,
+};
+
+use macroquad::prelude::*;
+
+use super::draw::{draw_hud_text, hud_text_width};
+use super::effects::BOSS_DEBRIS_COUNT;
+use super::waves::{AttackPattern, BossKind, BossMovement};
+use super::weapon::Weapon;
+use super::{CopterSimulation, RoflcopterAnimation};
+use crate::helper::*;
+use crate::simulation::Step;
+use crate::state::State;
+
+/// How long the victory is celebrated, before the game goes on.
+pub const VICTORY_DURATION: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone)]
+pub struct Boss {
+    /// The index of the boss' kind in the wave script.
+    pub kind: usize,
+    /// The top left corner of the boss' art.
+    pub position: Vec2,
+    /// The remaining health of each hit zone.
+    pub zones: Vec<usize>,
+    pub phase: usize,
+    /// The time since the last attack for each attack of the current phase.
+    pub attack_timers: Vec<Duration>,
+    /// The time since the boss spawned.
+    pub age: Duration,
+}
+
+/// A projectile fired by a boss.
+#[derive(Debug, Clone)]
+pub struct Projectile {
+    pub position: Vec2,
+    /// The velocity in pixels per second.
+    pub velocity: Vec2,
+}
+
+impl Boss {
+    pub fn health(&self) -> usize {
+        self.zones.iter().sum()
+    }
+
+    /// The size of the boss' art in pixels.
+    pub fn size(kind: &BossKind, glyph: Vec2) -> Vec2 {
+        text_size(&kind.art, glyph)
+    }
+
+    /// The area of a hit zone in pixels.
+    pub fn zone_area(&self, kind: &BossKind, zone: usize, glyph: Vec2) -> Rect {
+        let [column, row, width, height] = kind.zones[zone].area;
+        let position = self.position + Vec2::new(column as f32, row as f32) * glyph;
+        let size = Vec2::new(width as f32, height as f32) * glyph;
+
+        Rect::new(position.x, position.y, size.x, size.y)
+    }
+}
+
+impl CopterSimulation {
+    /// Let a boss enter from the right side of the screen.
+    pub fn spawn_boss(&mut self, step: &Step, kind: usize) {
+        let boss = &self.script.bosses[kind];
+        let size = Boss::size(boss, step.glyph);
+        self.boss = Some(Boss {
+            kind,
+            position: Vec2::new(step.window_width, (step.window_height - size.y) / 2.0),
+            zones: boss.zones.iter().map(|zone| zone.health).collect(),
+            phase: 0,
+            attack_timers: Vec::new(),
+            age: Duration::from_secs(0),
+        });
+    }
+
+    /// Move the boss, let it attack and check if it has been hit.
+    pub fn update_boss(&mut self, step: &Step) {
+        self.update_projectiles(step);
+        if let Some(ref mut timer) = self.victory {
+            *timer += step.delta;
+        }
+
+        let Some(mut boss) = self.boss.take() else {
+            return;
+        };
+        let kind = &self.script.bosses[boss.kind];
+        let size = Boss::size(kind, step.glyph);
+        boss.age += step.delta;
+
+        // Switch to the next phase, once enough damage has been taken.
+        let phase = kind.phase(boss.health() as f32 / kind.max_health().max(1) as f32);
+        if phase != boss.phase || boss.attack_timers.is_empty() {
+            boss.phase = phase;
+            let attacks = kind
+                .phases
+                .get(phase)
+                .map_or(0, |phase| phase.attacks.len());
+            boss.attack_timers = vec![Duration::from_secs(0); attacks];
+        }
+
+        // Fly in from the right, before doing anything else.
+        let home = step.window_width - size.x - step.glyph.x * 2.0;
+        if boss.position.x > home {
+            let speed = kind.speed * step.window_width;
+            boss.position.x = (boss.position.x - speed * step.delta_secs()).max(home);
+        } else if let Some(phase) = kind.phases.get(boss.phase) {
+            self.move_boss(step, &mut boss, phase.movement, size);
+
+            let muzzle = boss.position + Vec2::new(0.0, size.y / 2.0);
+            let aim = (self.copter_middle() - muzzle).normalize_or_zero();
+            for (attack, timer) in phase.attacks.iter().zip(boss.attack_timers.iter_mut()) {
+                *timer += step.delta;
+                if *timer < attack.interval {
+                    continue;
+                }
+                *timer = Duration::from_secs(0);
+
+                let speed = attack.speed * step.window_width;
+                for direction in attack_directions(attack.pattern, aim) {
+                    self.projectiles.push(Projectile {
+                        position: muzzle,
+                        velocity: direction * speed,
+                    });
+                }
+            }
+        }
+
+        let score = kind.score;
+        self.check_boss_hits(step, &mut boss);
+        if boss.health() == 0 {
+            self.register_kill(score);
+            self.explode_debris(step, boss.position + size / 2.0, BOSS_DEBRIS_COUNT);
+            self.projectiles.clear();
+            self.victory = Some(Duration::from_secs(0));
+        } else {
+            self.boss = Some(boss);
+        }
+    }
+
+    /// The middle of the first intact hit zone, once the boss is on screen.
+    pub fn boss_target(&self, step: &Step) -> Option<Vec2> {
+        let boss = self.boss.as_ref()?;
+        let kind = &self.script.bosses[boss.kind];
+        let zone = (0..boss.zones.len()).find(|zone| boss.zones[*zone] > 0)?;
+        let target = boss.zone_area(kind, zone, step.glyph).center();
+
+        outside_screen(step, target).is_none().then_some(target)
+    }
+
+    /// Whether the boss has been defeated and the victory has been celebrated.
+    pub fn boss_defeated(&self) -> bool {
+        self.victory.is_some_and(|timer| timer >= VICTORY_DURATION)
+    }
+
+    fn move_boss(&self, step: &Step, boss: &mut Boss, movement: BossMovement, size: Vec2) {
+        let middle = (step.window_height - size.y) / 2.0;
+        let age = boss.age.as_secs_f32();
+        let target = match movement {
+            BossMovement::Hover => middle + (age * 0.5 * TAU).sin() * step.window_height * 0.05,
+            BossMovement::Sweep => middle + (age * 0.2 * TAU).sin() * middle,
+            BossMovement::Chase => self.copter_middle().y - size.y / 2.0,
+        };
+
+        let max_distance =
+            self.script.bosses[boss.kind].speed * step.window_height * step.delta_secs();
+        let distance = (target - boss.position.y).clamp(-max_distance, max_distance);
+        boss.position.y =
+            (boss.position.y + distance).clamp(0.0, (step.window_height - size.y).max(0.0));
+    }
+
+    /// Shots, that hit an intact hit zone, damage it.
+    /// The rest of the boss' art absorbs shots without any damage.
+    fn check_boss_hits(&mut self, step: &Step, boss: &mut Boss) {
+        let kind = &self.script.bosses[boss.kind];
+        let size = Boss::size(kind, step.glyph);
+        let area = Rect::new(boss.position.x, boss.position.y, size.x, size.y);
+
+        let mut explosions = Vec::new();
+        self.shots.retain(|shot| {
+            if !area.contains(shot.position) {
+                return true;
+            }
+
+            let zone = (0..boss.zones.len()).find(|zone| {
+                boss.zones[*zone] > 0
+                    && boss
+                        .zone_area(kind, *zone, step.glyph)
+                        .contains(shot.position)
+            });
+            if let Some(zone) = zone {
+                boss.zones[zone] = boss.zones[zone].saturating_sub(shot.damage);
+            }
+            if shot.weapon == Weapon::Bomb {
+                explosions.push((shot.position, shot.damage));
+            }
+
+            false
+        });
+
+        for (position, damage) in explosions {
+            self.explode(step, position, damage);
+        }
+    }
+
+    /// A laser damages each intact hit zone, that it passes through.
+    pub fn hit_boss_along(&mut self, step: &Step, start: Vec2, end: Vec2, damage: usize) {
+        let Some(boss) = self.boss.as_mut() else {
+            return;
+        };
+        let kind = &self.script.bosses[boss.kind];
+
+        // Sample the beam in steps of half a glyph, so no zone is skipped.
+        let samples = (start.distance(end) / (step.glyph.x / 2.0).max(1.0)) as usize;
+        for zone in 0..boss.zones.len() {
+            let area = boss.zone_area(kind, zone, step.glyph);
+            let hit = (0..=samples).any(|sample| {
+                area.contains(start.lerp(end, sample as f32 / samples.max(1) as f32))
+            });
+            if hit {
+                boss.zones[zone] = boss.zones[zone].saturating_sub(damage);
+            }
+        }
+    }
+
+    /// Move all projectiles and remove the ones, that left the screen.
+    fn update_projectiles(&mut self, step: &Step) {
+        for projectile in self.projectiles.iter_mut() {
+            projectile.position += projectile.velocity * step.delta_secs();
+        }
+        self.projectiles
+            .retain(|projectile| outside_screen(step, projectile.position).is_none());
+    }
+}
+
+/// The directions of all projectiles of an attack.
+/// `aim` is the normalized direction towards the copter.
+pub fn attack_directions(pattern: AttackPattern, aim: Vec2) -> Vec<Vec2> {
+    let angle = vec2_to_radian(aim);
+    let angles: Vec<f32> = match pattern {
+        AttackPattern::Aimed => vec![angle],
+        AttackPattern::Spread { count, angle: fan } => {
+            let fan = fan.to_radians();
+            (0..count)
+                .map(|index| {
+                    if count == 1 {
+                        angle
+                    } else {
+                        angle - fan / 2.0 + fan * index as f32 / (count - 1) as f32
+                    }
+                })
+                .collect()
+        }
+        AttackPattern::Ring { count } => (0..count)
+            .map(|index| angle + 2.0 * PI * index as f32 / count as f32)
+            .collect(),
+    };
+
+    angles
+        .into_iter()
+        .map(|angle| Vec2::new(angle.cos(), angle.sin()))
+        .collect()
+}
+
+impl RoflcopterAnimation {
+    /// Draw the boss, its projectiles and its health bar.
+    /// Destroyed hit zones are blacked out.
+    pub fn draw_boss(&self, state: &State) {
+        let simulation = &self.simulation;
+        let glyph = Vec2::new(state.font_dimensions.width, state.font_dimensions.height);
+
+        if let Some(boss) = &simulation.boss {
+            let kind = &simulation.script.bosses[boss.kind];
+            draw_texture_ex(
+                self.textures.bosses[boss.kind],
+                boss.position.x,
+                boss.position.y,
+                WHITE,
+                DrawTextureParams {
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+            for (zone, health) in boss.zones.iter().enumerate() {
+                if *health == 0 {
+                    let area = boss.zone_area(kind, zone, glyph);
+                    draw_rectangle(area.x, area.y, area.w, area.h, BLACK);
+                }
+            }
+
+            self.draw_boss_health(state, boss, kind);
+        }
+
+        let texture = simulation
+            .boss
+            .as_ref()
+            .map(|boss| self.textures.projectiles[boss.kind]);
+        if let Some(texture) = texture {
+            for projectile in simulation.projectiles.iter() {
+                let position =
+                    projectile.position - Vec2::new(texture.width(), texture.height()) / 2.0;
+                draw_texture_ex(
+                    texture,
+                    position.x,
+                    position.y,
+                    WHITE,
+                    DrawTextureParams {
+                        flip_y: true,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        if simulation.victory.is_some() && !simulation.boss_defeated() {
+            let text = "BOSS DEFEATED";
+            let position = Vec2::new(
+                (state.window_width - hud_text_width(state, text)) / 2.0,
+                state.window_height / 2.0,
+            );
+            draw_hud_text(state, text, position, state.palette.colors[0]);
+        }
+    }
+
+    /// The boss' name and a health bar at the top of the screen.
+    fn draw_boss_health(&self, state: &State, boss: &Boss, kind: &BossKind) {
+        let margin = state.font_dimensions.width;
+        let width = state.window_width / 3.0;
+        let height = state.font_dimensions.height / 2.0;
+        let name = kind.name.to_uppercase();
+        let x = (state.window_width - width) / 2.0;
+        let y = margin + state.font_dimensions.height * 2.0;
+
+        draw_hud_text(
+            state,
+            &name,
+            Vec2::new(
+                (state.window_width - hud_text_width(state, &name)) / 2.0,
+                margin,
+            ),
+            WHITE,
+        );
+        let health = boss.health() as f32 / kind.max_health().max(1) as f32;
+        draw_rectangle(x, y, width * health, height, state.palette.colors[0]);
+        draw_rectangle_lines(x, y, width, height, 2.0, WHITE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attacks_are_aimed_at_the_copter() {
+        let aim = Vec2::new(-1.0, 0.0);
+
+        let aimed = attack_directions(AttackPattern::Aimed, aim);
+        assert_eq!(aimed.len(), 1);
+        assert!(aimed[0].distance(aim) < 0.001);
+
+        let spread = attack_directions(
+            AttackPattern::Spread {
+                count: 3,
+                angle: 90.0,
+            },
+            aim,
+        );
+        assert!(spread[1].distance(aim) < 0.001);
+        assert!((spread[0].angle_between(spread[2]).abs() - PI / 2.0).abs() < 0.001);
+
+        let ring = attack_directions(AttackPattern::Ring { count: 4 }, aim);
+        let sum: Vec2 = ring.iter().sum();
+        assert_eq!(ring.len(), 4);
+        assert!(sum.length() < 0.001);
+    }
+}
@@ -27,6 +27,28 @@ pub fn draw_roflcopter(
     )
 }
 
+/// Draw a line of the HUD with the current font.
+/// The position is the top left corner of the text.
+pub fn draw_hud_text(state: &State, text: &str, position: Vec2, color: Color) {
+    draw_text_ex(
+        text,
+        position.x,
+        position.y + state.font_dimensions.offset_y,
+        TextParams {
+            font: state.font,
+            font_size: state.font_size,
+            font_scale: 1.0,
+            color,
+            ..Default::default()
+        },
+    );
+}
+
+/// The width of a HUD line.
+pub fn hud_text_width(state: &State, text: &str) -> f32 {
+    measure_text(text, Some(state.font), state.font_size, 1.0).width
+}
+
 /// Generate the helicopter texture, depending on the directions.
 pub fn generate_copter_texture(
     state: &State,
@@ -35,9 +57,33 @@ pub fn generate_copter_texture(
 ) -> Texture2D {
     let text = get_ascii_art(&rotor_direction, &copter_direction);
 
-    texture_from_text(state, &text, state.font_size, None)
+    // Every column of the copter gets its own color.
+    let colors = state.palette.colors_for(grapheme_columns(&text));
+
+    texture_from_text(state, &text, state.font_size, Some(&colors))
 }
 
+/// Generate the texture of a boss, whose columns are colored just like the copter.
+pub fn generate_boss_texture(state: &State, art: &str) -> Texture2D {
+    let columns = art.lines().map(grapheme_columns).max().unwrap_or(0);
+    let colors = state.palette.colors_for(columns);
+
+    texture_from_text(state, art, state.font_size, Some(&colors))
+}
+
+/// Generate the big game over text, which uses the colors of the palette.
+pub fn generate_game_over_texture(state: &State) -> Texture2D {
+    let colors = state.palette.colors_for(grapheme_columns(GAME_OVER));
+
+    texture_from_text(state, GAME_OVER, state.font_size, Some(&colors))
+}
+
+const GAME_OVER: &str = r#"  ____    _    __  __ _____    _____     _______ ____
+ / ___|  / \  |  \/  | ____|  / _ \ \   / / ____|  _ \
+| |  _  / _ \ | |\/| |  _|   | | | \ \ / /|  _| | |_) |
+| |_| |/ ___ \| |  | | |___  | |_| |\ V / | |___|  _ <
+ \____/_/   \_\_|  |_|_____|  \___/  \_/  |_____|_| \_\"#;
+
 /// Get the correct ascii art, depending on the copter direction and rotor orientation.
 fn get_ascii_art(rotor_direction: &Side, copter_direction: &Side) -> String {
     match copter_direction {
@@ -0,0 +1,83 @@
+use std::{f32::consts::PI, time::Duration};
+
+use macroquad::prelude::*;
+
+use super::{CopterSimulation, RoflcopterAnimation};
+use crate::helper::*;
+use crate::simulation::Step;
+
+/// The ascii art of the debris of exploding enemies.
+pub const DEBRIS: [&str; 4] = ["*", "#", "+", "x"];
+/// The particles of an exploding enemy.
+pub const DEBRIS_COUNT: usize = 16;
+/// Bosses go out with a bigger bang.
+pub const BOSS_DEBRIS_COUNT: usize = 120;
+/// Explosions shouldn't bring the game to a halt.
+const MAX_PARTICLES: usize = 2000;
+/// The amount of smoke per second, once the copter's health is almost gone.
+const SMOKE_RATE: f32 = 30.0;
+
+pub fn particle_system() -> ParticleSystem {
+    ParticleSystem::new(0.5, MAX_PARTICLES)
+}
+
+/// Glowing debris, that flies in all directions and falls down.
+pub fn debris_emitter() -> Emitter {
+    Emitter {
+        shapes: (0..DEBRIS.len())
+            .map(|texture| ParticleShape::Glyph {
+                texture,
+                source: None,
+            })
+            .chain([ParticleShape::Quad])
+            .collect(),
+        speed: (0.05, 0.25),
+        lifetime: (Duration::from_millis(400), Duration::from_millis(1200)),
+        size: (0.4, 0.8),
+        spin: 8.0,
+        colors: vec![YELLOW, ORANGE, RED, WHITE],
+        fade_to: Color::new(0.3, 0.3, 0.3, 0.0),
+        ..Default::default()
+    }
+}
+
+/// Grey smoke, that slowly rises from the rotor.
+pub fn smoke_emitter() -> Emitter {
+    Emitter {
+        direction: -PI / 2.0,
+        spread: PI / 3.0,
+        speed: (0.01, 0.03),
+        lifetime: (Duration::from_secs(1), Duration::from_secs(2)),
+        size: (0.3, 0.7),
+        spin: 2.0,
+        weight: -0.05,
+        colors: vec![GRAY, DARKGRAY, LIGHTGRAY],
+        fade_to: Color::new(0.2, 0.2, 0.2, 0.0),
+        ..Default::default()
+    }
+}
+
+impl CopterSimulation {
+    /// Blow something up into a cloud of debris.
+    pub fn explode_debris(&mut self, step: &Step, position: Vec2, count: usize) {
+        self.debris
+            .burst(&mut self.particles, step, position, count);
+    }
+
+    /// Move all particles and let a damaged copter smoke.
+    /// The more health is missing, the more it smokes.
+    pub fn update_particles(&mut self, step: &Step) {
+        let damage = 1.0 - self.health as f32 / self.max_health.max(1) as f32;
+        self.smoke.rate = SMOKE_RATE * damage;
+        let rotor = self.copter_position() + Vec2::new(self.copter_size.x / 2.0, 0.0);
+        self.smoke.emit(&mut self.particles, step, rotor);
+
+        self.particles.update(step);
+    }
+}
+
+impl RoflcopterAnimation {
+    pub fn draw_particles(&self) {
+        self.simulation.particles.draw(&self.textures.debris);
+    }
+}
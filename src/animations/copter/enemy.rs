@@ -1,68 +1,198 @@
-use std::time::Duration;
+use std::{f32::consts::TAU, time::Duration};
 
 use macroquad::prelude::*;
 
-use super::RoflcopterAnimation;
+use super::waves::{Group, Movement};
+use super::{CopterSimulation, RoflcopterAnimation};
 use crate::helper::*;
-use crate::state::State;
+use crate::simulation::Step;
+
+/// The size of a cell of the enemy grid in glyphs.
+const ENEMY_GRID_CELLS: f32 = 8.0;
+/// Orbiting enemies close in with a fraction of their speed, once they reached their orbit.
+const ORBIT_APPROACH: f32 = 0.2;
 
 #[derive(Debug, Clone)]
 pub struct Enemy {
+    /// The index of this enemy's kind in the wave script.
+    pub kind: usize,
     pub position: Vec2,
+    /// The position on the straight path to the copter, which the movement pattern is based on.
+    pub anchor: Vec2,
+    /// The place in a formation relative to the anchor, with the x-axis pointing forward.
+    pub offset: Vec2,
+    /// The speed in pixels per second.
+    pub speed: f32,
     pub health: usize,
+    pub max_health: usize,
+    /// The time since the enemy spawned.
+    pub age: Duration,
+    /// Zigzagging enemies don't swing in sync.
+    pub phase: f32,
 }
 
-impl RoflcopterAnimation {
-    /// Update the enemy position and check collisions with shots.
-    pub fn update_enemies(&mut self) {
-        let copter_position = self.get_copter_position();
-        //let enemies_to_remove = Vec::new();
+impl Enemy {
+    pub fn new(kind: usize, position: Vec2, speed: f32, health: usize) -> Enemy {
+        Enemy {
+            kind,
+            position,
+            anchor: position,
+            offset: Vec2::ZERO,
+            speed,
+            health,
+            max_health: health,
+            age: Duration::from_secs(0),
+            phase: 0.0,
+        }
+    }
+
+    /// Move the enemy towards the target with the given movement pattern.
+    pub fn advance(&mut self, movement: Movement, target: Vec2, step: &Step) {
+        let delta = step.delta_secs();
+        self.age += step.delta;
+
+        let direction = (target - self.anchor).normalize_or_zero();
+        self.position = match movement {
+            Movement::Straight => {
+                self.anchor += direction * self.speed * delta;
+                self.anchor
+            }
+            Movement::Zigzag {
+                amplitude,
+                frequency,
+            } => {
+                self.anchor += direction * self.speed * delta;
+                let sway = (self.age.as_secs_f32() * frequency * TAU + self.phase).sin();
+                self.anchor + direction.perp() * sway * amplitude * step.window_width
+            }
+            Movement::Orbit {
+                radius,
+                angular_speed,
+            } => {
+                let relative = self.anchor - target;
+                let approach = if relative.length() > radius * step.window_width {
+                    self.speed
+                } else {
+                    self.speed * ORBIT_APPROACH
+                };
+                let distance = (relative.length() - approach * delta).max(0.0);
+                let angle = vec2_to_radian(relative) + angular_speed * delta;
+
+                self.anchor = target + Vec2::new(angle.cos(), angle.sin()) * distance;
+                self.anchor
+            }
+            Movement::Formation { .. } => {
+                self.anchor += direction * self.speed * delta;
+                self.anchor + rotate_vec2(self.offset, vec2_to_radian(direction))
+            }
+        };
+    }
+}
+
+impl CopterSimulation {
+    /// Move all enemies towards the copter.
+    pub fn update_enemies(&mut self, step: &Step) {
+        let copter_middle = self.copter_middle();
 
         for enemy in self.enemies.iter_mut() {
-            // Check if the enemy hit the player
-            //if middle direction.length() <= 40
+            let movement = self.script.enemies[enemy.kind].movement;
+            enemy.advance(movement, copter_middle, step);
+        }
+    }
+
+    /// The box around an enemy's art, which is turned towards the copter just like its texture.
+    pub fn enemy_obb(&self, step: &Step, enemy: &Enemy) -> Obb {
+        let size = text_size(&self.script.enemies[enemy.kind].art, step.glyph);
+        let rotation = vec2_to_radian(self.copter_middle() - enemy.position);
 
-            let direction = copter_position - enemy.position;
-            let distance = (direction / direction.length()) * self.enemy_speed * get_frame_time();
-            enemy.position += distance;
+        Obb::from_texture(enemy.position, size, rotation)
+    }
+
+    /// Sort all enemies into a grid, so shots only need to check the enemies close to them.
+    /// Returns the grid and the box of each enemy.
+    pub fn enemy_grid(&self, step: &Step) -> (SpatialGrid, Vec<Obb>) {
+        let mut grid = SpatialGrid::new(step.glyph.x * ENEMY_GRID_CELLS);
+        let boxes: Vec<Obb> = self
+            .enemies
+            .iter()
+            .map(|enemy| self.enemy_obb(step, enemy))
+            .collect();
+        for (index, obb) in boxes.iter().enumerate() {
+            grid.insert(index, obb.bounds());
         }
+
+        (grid, boxes)
     }
 
-    /// Update the enemy spawn timer and spawn enemies, if it's time.
-    pub fn spawn_enemies(&mut self, state: &State) {
+    /// Update the wave timer and spawn the next wave of the script, if it's time.
+    pub fn spawn_enemies(&mut self, step: &Step) {
         // Global flag to stop spawning enemies.
-        if !self.spawn_enemies {
+        // The script also waits, until the current boss has been defeated and the victory celebrated.
+        let celebrating = self.victory.is_some() && !self.boss_defeated();
+        if !self.spawn_enemies || self.boss.is_some() || celebrating {
             return;
         }
 
         // Update timer and check if it's time to spawn a wave.
-        self.enemy_wave_timer += delta_duration();
-        if self.enemy_wave_timer < self.enemy_wave_timeout {
+        let wave = &self.script.waves[self.wave_index];
+        self.enemy_wave_timer += step.delta;
+        if self.enemy_wave_timer < self.script.escalation.delay(wave.delay, self.round) {
             return;
         } else {
             self.enemy_wave_timer = Duration::from_secs(0);
         }
 
-        // Spawn enemies in a cluster around a random point outside the screen.
-        let offset = state.window_width / 10.0;
-        let cluster_center = random_position_outside_screen(state, offset);
-        for _ in 0..self.enemy_wave_size {
-            let position = cluster_center + random_vector_with_lenght(offset);
-            self.enemies.push(Enemy {
-                position,
-                health: self.enemy_max_health,
-            });
+        let wave = wave.clone();
+        for group in wave.groups.iter() {
+            self.spawn_group(step, group);
+        }
+        if let Some(boss) = wave.boss {
+            self.spawn_boss(step, boss);
+        }
+
+        // Start over with harder waves, once the script is done.
+        self.wave_index += 1;
+        if self.wave_index >= self.script.waves.len() {
+            self.wave_index = 0;
+            self.round += 1;
         }
     }
 
-    /// Update the enemy spawn timer and spawn enemies, if it's time.
+    /// Spawn a group around a random point outside the screen.
+    fn spawn_group(&mut self, step: &Step, group: &Group) {
+        let kind = &self.script.enemies[group.enemy];
+        let escalation = &self.script.escalation;
+        let speed = escalation.speed(kind.speed, self.round) * step.window_width;
+        let health = escalation.health(kind.health, self.round);
+        let count = escalation.count(group.count, self.round);
+
+        let offset = step.window_width / 10.0;
+        let cluster_center = random_position_outside_screen(step, offset);
+        for index in 0..count {
+            let mut enemy = Enemy::new(group.enemy, cluster_center, speed, health);
+            match kind.movement.formation_offset(index, step.window_width) {
+                Some(formation_offset) => enemy.offset = formation_offset,
+                None => {
+                    enemy.anchor += random_vector_with_lenght(step.rng, offset);
+                    enemy.position = enemy.anchor;
+                }
+            }
+            enemy.phase = step.rng.gen_range(0.0, TAU);
+            self.enemies.push(enemy);
+        }
+    }
+}
+
+impl RoflcopterAnimation {
+    /// Draw all enemies, pointing towards the copter.
     pub fn draw_enemies(&self) {
-        let copter_position = self.get_copter_position();
-        for enemy in self.enemies.iter() {
-            let direction = copter_position - enemy.position;
-            let health_percent = enemy.health as f32 / self.enemy_max_health as f32;
+        let simulation = &self.simulation;
+        let copter_middle = simulation.copter_middle();
+        for enemy in simulation.enemies.iter() {
+            let direction = copter_middle - enemy.position;
+            let health_percent = enemy.health as f32 / enemy.max_health.max(1) as f32;
             draw_texture_ex(
-                self.textures.enemy,
+                self.textures.enemies[enemy.kind],
                 enemy.position.x,
                 enemy.position.y,
                 Color::new(1.0, 1.0 * health_percent, 1.0 * health_percent, 1.0),
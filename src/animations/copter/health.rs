@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use macroquad::prelude::*;
+
+use super::draw::{draw_hud_text, hud_text_width};
+use super::effects::DEBRIS_COUNT;
+use super::{CopterSimulation, RoflcopterAnimation, RoflcopterState};
+use crate::simulation::Step;
+use crate::state::State;
+
+/// The hitbox is a little smaller than the copter's texture,
+/// as the ascii art doesn't fill its whole texture.
+const HITBOX_SCALE: f32 = 0.8;
+
+impl CopterSimulation {
+    /// The area in which enemies hit the copter.
+    pub fn copter_hitbox(&self) -> Rect {
+        let size = self.copter_size * HITBOX_SCALE;
+        let position = self.copter_middle() - size / 2.0;
+
+        Rect::new(position.x, position.y, size.x, size.y)
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        !self.invulnerable.is_zero()
+    }
+
+    /// Enemies, that reach the copter, crash into it.
+    /// The copter only takes damage, if it isn't invulnerable from the last hit.
+    pub fn check_enemy_collisions(&mut self, step: &Step) {
+        self.invulnerable = self.invulnerable.saturating_sub(step.delta);
+
+        let hitbox = self.copter_hitbox();
+        let hits = self.enemies.len() + self.projectiles.len();
+        let crashed: Vec<Vec2> = self
+            .enemies
+            .iter()
+            .map(|enemy| enemy.position)
+            .filter(|position| hitbox.contains(*position))
+            .collect();
+        for position in crashed {
+            self.explode_debris(step, position, DEBRIS_COUNT);
+        }
+        self.enemies
+            .retain(|enemy| !hitbox.contains(enemy.position));
+        self.projectiles
+            .retain(|projectile| !hitbox.contains(projectile.position));
+        if self.enemies.len() + self.projectiles.len() == hits || self.is_invulnerable() {
+            return;
+        }
+
+        self.invulnerable = self.invulnerability;
+        self.health -= 1;
+        if self.health > 0 {
+            return;
+        }
+
+        self.lives -= 1;
+        if self.lives == 0 {
+            self.end_game();
+        } else {
+            self.health = self.max_health;
+        }
+    }
+
+    /// Stop the game and let manual pilots enter their name, if they made it into the high scores.
+    pub fn end_game(&mut self) {
+        self.game_over = Some(Duration::from_secs(0));
+        self.enemies.clear();
+        self.shots.clear();
+        self.projectiles.clear();
+        self.power_ups.clear();
+        self.beams.clear();
+        self.explosions.clear();
+        self.particles.clear();
+        self.boss = None;
+        self.combo = 0;
+        self.reset_weapon();
+
+        if !self.autopilot && self.high_scores.qualifies(self.score) {
+            self.name_entry = Some(String::new());
+        }
+    }
+
+    /// Wait for the player to restart the game.
+    /// The autopilot restarts on its own, so unattended screens keep running.
+    pub fn update_game_over(&mut self, step: &Step) {
+        if self.name_entry.is_some() {
+            self.enter_name(step);
+            return;
+        }
+
+        let Some(ref mut timer) = self.game_over else {
+            return;
+        };
+        *timer += step.delta;
+
+        let restart = if self.autopilot {
+            *timer >= self.respawn_timeout
+        } else {
+            step.input.is_key_pressed(KeyCode::Space)
+        };
+        if restart {
+            self.respawn(step);
+        }
+    }
+
+    /// Start a new game with a fresh copter in the middle of the screen.
+    pub fn respawn(&mut self, step: &Step) {
+        self.game_over = None;
+        self.health = self.max_health;
+        self.lives = self.max_lives;
+        self.score = 0;
+        self.combo = 0;
+        self.new_high_score = None;
+        self.invulnerable = self.invulnerability;
+        self.velocity = Vec2::ZERO;
+        self.enemies.clear();
+        self.shots.clear();
+        self.projectiles.clear();
+        self.power_ups.clear();
+        self.beams.clear();
+        self.explosions.clear();
+        self.particles.clear();
+        self.reset_weapon();
+        self.boss = None;
+        self.victory = None;
+        self.enemy_wave_timer = Duration::from_secs(0);
+        self.wave_index = 0;
+        self.round = 0;
+
+        let middle = Vec2::new(step.window_width, step.window_height) / 2.0;
+        self.roflcopter_state = RoflcopterState::Hovering {
+            duration: Duration::from_secs(1),
+            timer: Duration::from_secs(0),
+            position: middle - self.copter_size / 2.0,
+            copter_direction: self.copter_direction(),
+        };
+    }
+}
+
+impl RoflcopterAnimation {
+    /// Draw the remaining lives and health in the top right corner.
+    pub fn draw_health(&self, state: &State) {
+        let simulation = &self.simulation;
+        let lives = "<3 ".repeat(simulation.lives);
+        let health = format!(
+            "[{}{}]",
+            "#".repeat(simulation.health),
+            " ".repeat(simulation.max_health - simulation.health)
+        );
+        let text = format!("{lives}{health}");
+
+        let margin = state.font_dimensions.width;
+        let position = Vec2::new(
+            state.window_width - hud_text_width(state, &text) - margin,
+            margin,
+        );
+        draw_hud_text(state, &text, position, WHITE);
+    }
+
+    /// Draw the game over screen in the middle of the screen.
+    /// Below it are the final score and the high score table.
+    pub fn draw_game_over(&self, state: &State, timer: Duration) {
+        let texture = self.textures.game_over;
+        let line_height = state.font_dimensions.height * 1.5;
+        let position = Vec2::new(
+            (state.window_width - texture.width()) / 2.0,
+            state.window_height / 4.0 - texture.height() / 2.0,
+        );
+        draw_texture_ex(
+            texture,
+            position.x,
+            position.y,
+            WHITE,
+            DrawTextureParams {
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+
+        let simulation = &self.simulation;
+        let text = if simulation.name_entry.is_some() {
+            "Type your name and press ENTER".to_string()
+        } else if simulation.autopilot {
+            let remaining = simulation.respawn_timeout.saturating_sub(timer);
+            format!("Respawning in {}...", remaining.as_secs() + 1)
+        } else {
+            "Press SPACE to fly again".to_string()
+        };
+
+        let mut y = position.y + texture.height() + line_height;
+        for line in [format!("Score: {}", simulation.score), text] {
+            let x = (state.window_width - hud_text_width(state, &line)) / 2.0;
+            draw_hud_text(state, &line, Vec2::new(x, y), WHITE);
+            y += line_height;
+        }
+
+        self.draw_high_scores(state, y + line_height);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use macroquad::prelude::KeyCode;
+use serde::{Deserialize, Serialize};
+
+/// The amount of scores, that are kept in the table.
+pub const MAX_ENTRIES: usize = 10;
+/// The maximum amount of characters of a name.
+pub const MAX_NAME_LENGTH: usize = 12;
+
+/// While a name is typed, none of the letters should trigger any hotkeys.
+pub const NAME_ENTRY_KEYS: [KeyCode; 28] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Backspace,
+    KeyCode::Enter,
+];
+
+#[derive(thiserror::Error, Debug)]
+pub enum HighScoreError {
+    #[error("Couldn't access high scores {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Couldn't parse high scores {path:?}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Couldn't serialize high scores: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: usize,
+}
+
+/// The content of the high score file.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct HighScoreFile {
+    #[serde(default)]
+    scores: Vec<HighScore>,
+}
+
+/// The best scores, sorted from best to worst.
+///
+/// Without a path, the table only lives in memory.
+#[derive(Debug, Clone, Default)]
+pub struct HighScores {
+    path: Option<PathBuf>,
+    pub scores: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Load the high scores from a toml file.
+    /// A missing file is fine, it'll be created once the first score is saved.
+    pub fn load(path: &Path) -> Result<HighScores, HighScoreError> {
+        let mut high_scores = HighScores {
+            path: Some(path.to_path_buf()),
+            scores: Vec::new(),
+        };
+        if !path.exists() {
+            return Ok(high_scores);
+        }
+
+        let content = fs::read_to_string(path).map_err(|source| HighScoreError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let file: HighScoreFile =
+            toml::from_str(&content).map_err(|source| HighScoreError::Parse {
+                path: path.to_path_buf(),
+                source,
+            })?;
+
+        high_scores.scores = file.scores;
+        high_scores
+            .scores
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        high_scores.scores.truncate(MAX_ENTRIES);
+
+        Ok(high_scores)
+    }
+
+    /// Whether a score is good enough to get into the table.
+    pub fn qualifies(&self, score: usize) -> bool {
+        score > 0
+            && (self.scores.len() < MAX_ENTRIES
+                || self.scores.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Add a new score and return its position in the table.
+    /// Newer scores are ranked below older ones with the same score.
+    pub fn insert(&mut self, name: String, score: usize) -> Option<usize> {
+        let index = self.scores.partition_point(|entry| entry.score >= score);
+        if index >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.scores.insert(index, HighScore { name, score });
+        self.scores.truncate(MAX_ENTRIES);
+
+        Some(index)
+    }
+
+    /// Write the table to its file, if it has one.
+    pub fn save(&self) -> Result<(), HighScoreError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let file = HighScoreFile {
+            scores: self.scores.clone(),
+        };
+        let content = toml::to_string(&file)?;
+        let io_error = |source| HighScoreError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(path, content).map_err(io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(scores: &[usize]) -> HighScores {
+        HighScores {
+            path: None,
+            scores: scores
+                .iter()
+                .map(|score| HighScore {
+                    name: format!("P{score}"),
+                    score: *score,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn scores_are_inserted_in_order() {
+        let mut high_scores = table(&[300, 200, 100]);
+
+        assert_eq!(high_scores.insert("NEW".to_string(), 250), Some(1));
+        assert_eq!(high_scores.insert("TIE".to_string(), 100), Some(4));
+
+        let scores: Vec<usize> = high_scores.scores.iter().map(|entry| entry.score).collect();
+        assert_eq!(scores, vec![300, 250, 200, 100, 100]);
+        assert_eq!(high_scores.scores[3].name, "P100");
+    }
+
+    #[test]
+    fn full_tables_only_take_better_scores() {
+        let mut high_scores = table(&[1000, 900, 800, 700, 600, 500, 400, 300, 200, 100]);
+
+        assert!(!high_scores.qualifies(100));
+        assert!(high_scores.qualifies(101));
+        assert!(!table(&[]).qualifies(0));
+
+        assert_eq!(high_scores.insert("LOW".to_string(), 50), None);
+        assert_eq!(high_scores.insert("MID".to_string(), 550), Some(5));
+        assert_eq!(high_scores.scores.len(), MAX_ENTRIES);
+        assert_eq!(high_scores.scores.last().unwrap().score, 200);
+    }
+
+    #[test]
+    fn high_score_files_round_trip() {
+        let file = HighScoreFile {
+            scores: table(&[300, 100]).scores,
+        };
+        let content = toml::to_string(&file).unwrap();
+        let parsed: HighScoreFile = toml::from_str(&content).unwrap();
+
+        assert_eq!(parsed.scores, file.scores);
+    }
+}
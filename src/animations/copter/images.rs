@@ -1,6 +1,9 @@
 use macroquad::prelude::{Texture2D, Vec2};
 
-use super::draw::generate_copter_texture;
+use super::draw::{generate_boss_texture, generate_copter_texture, generate_game_over_texture};
+use super::effects::DEBRIS;
+use super::waves::WaveScript;
+use super::weapon::Weapon;
 use crate::{helper::*, state::State};
 
 /// A struct used to store dynamically generated images of the roflcopter.
@@ -10,32 +13,94 @@ pub struct Textures {
     pub right_copter_left_rotor: Texture2D,
     pub left_copter_right_rotor: Texture2D,
     pub left_copter_left_rotor: Texture2D,
-    pub shot: Texture2D,
-    pub enemy: Texture2D,
+    /// One texture for the shots of each weapon.
+    pub shots: Vec<Texture2D>,
+    /// One texture for the power-up of each weapon.
+    pub power_ups: Vec<Texture2D>,
+    pub explosion: Texture2D,
+    /// The glyphs of the debris particles.
+    pub debris: Vec<Texture2D>,
+    /// One texture for each enemy kind of the wave script.
+    pub enemies: Vec<Texture2D>,
+    /// One texture for each boss of the wave script.
+    pub bosses: Vec<Texture2D>,
+    /// The projectiles of each boss.
+    pub projectiles: Vec<Texture2D>,
+    pub game_over: Texture2D,
 }
 
-impl Textures {
-    pub fn new(state: &State) -> Textures {
-        let enemy = "===>";
+/// The ascii art of the debris of an explosion.
+const EXPLOSION: &str = "*";
 
+impl Textures {
+    pub fn new(state: &State, script: &WaveScript) -> Textures {
         Textures {
             right_copter_right_rotor: generate_copter_texture(state, Side::Right, Side::Right),
             right_copter_left_rotor: generate_copter_texture(state, Side::Right, Side::Left),
             left_copter_right_rotor: generate_copter_texture(state, Side::Left, Side::Right),
             left_copter_left_rotor: generate_copter_texture(state, Side::Left, Side::Left),
-            shot: texture_from_text(state, "=>", state.font_size, None),
-            enemy: texture_from_text(state, enemy, state.font_size, None),
+            shots: Weapon::ALL
+                .iter()
+                .map(|weapon| texture_from_text(state, weapon.art(), state.font_size, None))
+                .collect(),
+            power_ups: Weapon::ALL
+                .iter()
+                .map(|weapon| {
+                    texture_from_text(state, weapon.power_up_art(), state.font_size, None)
+                })
+                .collect(),
+            explosion: texture_from_text(state, EXPLOSION, state.font_size, None),
+            debris: DEBRIS
+                .iter()
+                .map(|glyph| texture_from_text(state, glyph, state.font_size, None))
+                .collect(),
+            enemies: script
+                .enemies
+                .iter()
+                .map(|kind| texture_from_text(state, &kind.art, state.font_size, None))
+                .collect(),
+            bosses: script
+                .bosses
+                .iter()
+                .map(|kind| generate_boss_texture(state, &kind.art))
+                .collect(),
+            projectiles: script
+                .bosses
+                .iter()
+                .map(|kind| texture_from_text(state, &kind.projectile, state.font_size, None))
+                .collect(),
+            game_over: generate_game_over_texture(state),
         }
     }
 
     /// Dynamically update the copter images.
-    /// This is necessary, if we dynamically change our font size.
+    /// This is necessary, if we dynamically change our font or font size.
     ///  For instance, during window resizes.
-    pub fn update(&mut self, state: &State) {
-        self.right_copter_right_rotor = generate_copter_texture(state, Side::Right, Side::Right);
-        self.right_copter_left_rotor = generate_copter_texture(state, Side::Right, Side::Left);
-        self.left_copter_right_rotor = generate_copter_texture(state, Side::Left, Side::Right);
-        self.left_copter_left_rotor = generate_copter_texture(state, Side::Left, Side::Left);
+    pub fn update(&mut self, state: &State, script: &WaveScript) {
+        self.delete();
+        *self = Textures::new(state, script);
+    }
+
+    /// Free all textures. They mustn't be drawn afterwards.
+    pub fn delete(&self) {
+        for texture in [
+            self.right_copter_right_rotor,
+            self.right_copter_left_rotor,
+            self.left_copter_right_rotor,
+            self.left_copter_left_rotor,
+            self.explosion,
+            self.game_over,
+        ]
+        .iter()
+        .chain(self.shots.iter())
+        .chain(self.power_ups.iter())
+        .chain(self.debris.iter())
+        .chain(self.enemies.iter())
+        .chain(self.bosses.iter())
+        .chain(self.projectiles.iter())
+        {
+            texture.delete();
+        }
     }
 
     pub fn copter_dimensions(&self) -> Vec2 {
@@ -45,8 +110,9 @@ impl Textures {
         )
     }
 
-    pub fn texture(&self) -> &Texture2D {
-        &self.right_copter_right_rotor
+    pub fn shot_dimensions(&self) -> Vec2 {
+        let shot = self.shots[Weapon::Blaster.index()];
+        Vec2::new(shot.width(), shot.height())
     }
 
     /// Simple helper, which gets the correct texture for a copter and rotor orientation.
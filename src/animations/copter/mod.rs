@@ -1,39 +1,36 @@
 use std::time::Duration;
 
+use log::warn;
 use macroquad::prelude::*;
 
+mod boss;
 mod draw;
+mod effects;
 mod enemy;
+mod health;
+mod highscores;
 mod images;
 mod roflcopter;
+mod score;
 mod shot;
+mod simulation;
+mod waves;
+mod weapon;
 
-use self::enemy::*;
 use self::images::*;
-use self::shot::*;
+pub use self::simulation::{CopterSimulation, AUTOPILOT_KEY, MANUAL_KEYS};
+use self::waves::WaveScript;
+use super::Animation;
 use crate::helper::*;
+use crate::simulation::Step;
 use crate::state::State;
 
+/// The drawing layer of the copter.
+/// All game logic happens in the [CopterSimulation].
 #[derive(Debug, Clone)]
 pub struct RoflcopterAnimation {
     pub textures: Textures,
-
-    pub roflcopter_state: RoflcopterState,
-    rotor_direction: Side,
-    rotor_duration: Duration,
-    rotor_timer: Duration,
-
-    shot_timeout: Duration,
-    shot_timer: Duration,
-    shots: Vec<Shot>,
-
-    enemies: Vec<Enemy>,
-    spawn_enemies: bool,
-    enemy_speed: f32,
-    enemy_max_health: usize,
-    enemy_wave_size: usize,
-    enemy_wave_timeout: Duration,
-    enemy_wave_timer: Duration,
+    pub simulation: CopterSimulation,
 }
 
 #[derive(Debug, Clone)]
@@ -52,52 +49,113 @@ pub enum RoflcopterState {
 
 impl RoflcopterAnimation {
     pub fn new(state: &State, position: Vec2) -> RoflcopterAnimation {
-        let copter_state = RoflcopterState::Flying {
+        let config = &state.config.copter;
+        let script = WaveScript::from_config(config).unwrap_or_else(|err| {
+            warn!("{err}");
+            WaveScript::classic(config)
+        });
+        let textures = Textures::new(state, &script);
+        let mut simulation = CopterSimulation::new(
+            config,
+            script,
             position,
-            dest: Vec2::new(100.0, 100.0),
-        };
+            textures.copter_dimensions(),
+            textures.shot_dimensions(),
+        );
+        simulation.high_scores = score::load_high_scores(config);
 
         RoflcopterAnimation {
-            rotor_direction: Side::Left,
-            rotor_duration: Duration::from_millis(200),
-            rotor_timer: Duration::from_secs(0),
-
-            textures: Textures::new(state),
-            roflcopter_state: copter_state,
-
-            shot_timeout: Duration::from_millis(300),
-            shot_timer: Duration::from_secs(0),
-            shots: vec![],
-
-            enemies: vec![],
-            spawn_enemies: true,
-            enemy_speed: state.window_width / 20.0,
-            enemy_max_health: 3,
-            enemy_wave_size: 12,
-            enemy_wave_timeout: Duration::from_secs(10),
-            enemy_wave_timer: Duration::from_secs(0),
+            textures,
+            simulation,
         }
     }
+}
 
-    pub fn update(&mut self, state: &State) {
-        self.update_shots(state);
-        self.spawn_enemies(state);
-        self.update_enemies();
-
-        self.update_roflcopter(state);
+impl Animation for RoflcopterAnimation {
+    fn update(&mut self, state: &State) {
+        self.simulation.step(&Step::from_state(state));
     }
 
     /// Draw the copter depending on the current animation state.
-    pub fn draw(&self, state: &State) {
+    fn draw(&self, state: &State) {
+        if let Some(timer) = self.simulation.game_over {
+            self.draw_game_over(state, timer);
+            return;
+        }
+
+        self.draw_power_ups(state);
         self.draw_shots();
+        self.draw_beams();
         self.draw_enemies();
+        self.draw_explosions();
+        self.draw_particles();
+        self.draw_boss(state);
         self.draw_roflcopter(state);
+        self.draw_score(state);
+        self.draw_weapon(state);
+        self.draw_health(state);
+    }
+
+    /// A won boss fight ends the animation early.
+    fn is_finished(&self) -> bool {
+        self.simulation.end_after_boss && self.simulation.boss_defeated()
     }
 
-    fn get_copter_position(&self) -> Vec2 {
-        match self.roflcopter_state {
-            RoflcopterState::Flying { position, .. } => position,
-            RoflcopterState::Hovering { position, .. } => position,
+    fn reserved_keys(&self) -> &[KeyCode] {
+        if self.simulation.name_entry.is_some() {
+            &highscores::NAME_ENTRY_KEYS
+        } else if self.simulation.autopilot {
+            &[]
+        } else {
+            &MANUAL_KEYS
         }
     }
+
+    fn debug_info(&self) -> Vec<String> {
+        let mode = if self.simulation.autopilot {
+            "autopilot"
+        } else {
+            "manual"
+        };
+        let mut info = vec![
+            format!("Flight: {mode} (toggle with M)"),
+            format!(
+                "Lives: {}, Health: {}",
+                self.simulation.lives, self.simulation.health
+            ),
+            format!(
+                "Score: {}, Combo: {}",
+                self.simulation.score, self.simulation.combo
+            ),
+            format!(
+                "Weapon: {} (level {})",
+                self.simulation.weapon.name(),
+                self.simulation.weapon_level
+            ),
+        ];
+        if let Some(boss) = &self.simulation.boss {
+            let kind = &self.simulation.script.bosses[boss.kind];
+            info.push(format!(
+                "Boss: {} ({}/{}, phase {})",
+                kind.name,
+                boss.health(),
+                kind.max_health(),
+                boss.phase + 1
+            ));
+        }
+
+        info
+    }
+
+    fn on_resize(&mut self, state: &State) {
+        self.textures.update(state, &self.simulation.script);
+        self.simulation.resize(
+            self.textures.copter_dimensions(),
+            self.textures.shot_dimensions(),
+        );
+    }
+
+    fn on_exit(&mut self, _state: &State) {
+        self.textures.delete();
+    }
 }
@@ -4,21 +4,27 @@ use std::{
     time::Duration,
 };
 
-use macroquad::{prelude::*, rand::gen_range};
+use macroquad::prelude::*;
 
-use super::{draw::draw_roflcopter, RoflcopterAnimation, RoflcopterState};
+use super::{draw::draw_roflcopter, CopterSimulation, RoflcopterAnimation, RoflcopterState};
 use crate::helper::*;
+use crate::simulation::Step;
 use crate::state::State;
 
-impl RoflcopterAnimation {
+/// How fast the copter accelerates during manual flight, relative to its maximum speed.
+const MANUAL_ACCELERATION: f32 = 4.0;
+/// How much of its speed the copter loses per second, once no key is pressed.
+const MANUAL_DRAG: f32 = 2.0;
+
+impl CopterSimulation {
     /// Move the helicopter and tick any timers.
     ///
     /// This logic is also responsible for setting the next internal state once the previous
     /// animation has finished.
-    pub fn update_roflcopter(&mut self, state: &State) {
+    pub fn update_roflcopter(&mut self, step: &Step) {
         // This is the rotor animation.
         // This animation is always active.
-        self.rotor_timer = self.rotor_timer.checked_add(delta_duration()).unwrap();
+        self.rotor_timer = self.rotor_timer.checked_add(step.delta).unwrap();
         if self.rotor_timer > self.rotor_duration {
             match self.rotor_direction {
                 Side::Left => self.rotor_direction = Side::Right,
@@ -27,6 +33,11 @@ impl RoflcopterAnimation {
             self.rotor_timer = Duration::from_secs(0);
         }
 
+        if !self.autopilot {
+            self.steer(step);
+            return;
+        }
+
         // Update state dependant variables.
         match self.roflcopter_state {
             RoflcopterState::Flying {
@@ -35,11 +46,11 @@ impl RoflcopterAnimation {
             } => {
                 // Update the helicopter's position.
                 // The speed per second is relative to the screen width.
-                let speed = state.window_width / 4.0;
+                let speed = step.window_width / 4.0;
                 // Calculate the traveled distance for this frame
                 let direction = dest.sub(*position);
                 let normalized = direction.normalize();
-                let traveling = normalized * speed * get_frame_time();
+                let traveling = normalized * speed * step.delta_secs();
 
                 *position = position.add(traveling);
 
@@ -52,7 +63,7 @@ impl RoflcopterAnimation {
                     };
 
                     self.roflcopter_state = RoflcopterState::Hovering {
-                        duration: Duration::from_secs(gen_range(1, 3)),
+                        duration: Duration::from_secs(step.rng.gen_range(1, 3)),
                         timer: Duration::from_secs(0),
                         position: *position,
                         copter_direction,
@@ -65,7 +76,7 @@ impl RoflcopterAnimation {
                 ref position,
                 ..
             } => {
-                *timer = timer.checked_add(delta_duration()).unwrap();
+                *timer = timer.checked_add(step.delta).unwrap();
                 // Wait until we're done hovering.
                 if *timer < *duration {
                     return;
@@ -73,25 +84,97 @@ impl RoflcopterAnimation {
 
                 // We're done hovering, pick a random position on the screen.
                 // We only pick positions, where the copter can be fully seen.
-                let height = state.window_height;
-                let width = state.window_width;
+                // Power-ups are too good to pass up though.
+                let height = step.window_height;
+                let width = step.window_width;
 
-                let dimensions = self.textures.copter_dimensions();
+                let dimensions = self.copter_size;
+                let rng = step.rng;
 
+                let dest = self.power_up_dest(step).unwrap_or_else(|| {
+                    Vec2::new(
+                        rng.gen_range(0.0, width - dimensions.x),
+                        rng.gen_range(0.0, height - dimensions.y),
+                    )
+                });
                 self.roflcopter_state = RoflcopterState::Flying {
                     position: *position,
-                    dest: Vec2::new(
-                        gen_range(0.0, width - dimensions.x),
-                        gen_range(0.0, height - dimensions.y),
-                    ),
+                    dest,
                 };
             }
         }
     }
 
+    /// Fly with the keyboard.
+    /// The copter accelerates into the steering direction and slowly drifts to a halt,
+    /// once no key is pressed anymore.
+    fn steer(&mut self, step: &Step) {
+        let dt = step.delta_secs();
+        // The same speed as the autopilot.
+        let max_speed = step.window_width / 4.0;
+        let steering = step.input.steering().normalize_or_zero();
+
+        self.velocity += steering * max_speed * MANUAL_ACCELERATION * dt;
+        if steering == Vec2::ZERO {
+            self.velocity *= (1.0 - MANUAL_DRAG * dt).max(0.0);
+        }
+        self.velocity = self.velocity.clamp_length_max(max_speed);
+
+        // Stop at the edges, so the copter can always be fully seen.
+        let max_position =
+            (Vec2::new(step.window_width, step.window_height) - self.copter_size).max(Vec2::ZERO);
+        let position = self.copter_position() + self.velocity * dt;
+        let clamped = position.clamp(Vec2::ZERO, max_position);
+        if clamped.x != position.x {
+            self.velocity.x = 0.0;
+        }
+        if clamped.y != position.y {
+            self.velocity.y = 0.0;
+        }
+        let position = clamped;
+
+        let copter_direction = match steering.x {
+            x if x < 0.0 => Side::Left,
+            x if x > 0.0 => Side::Right,
+            _ => self.copter_direction(),
+        };
+
+        // Slow copters hover, just like they do with the autopilot.
+        if steering == Vec2::ZERO && self.velocity.length() < max_speed * 0.05 {
+            self.velocity = Vec2::ZERO;
+            let timer = match self.roflcopter_state {
+                RoflcopterState::Hovering { timer, .. } => timer + step.delta,
+                RoflcopterState::Flying { .. } => Duration::from_secs(0),
+            };
+            self.roflcopter_state = RoflcopterState::Hovering {
+                duration: Duration::MAX,
+                timer,
+                position,
+                copter_direction,
+            };
+            return;
+        }
+
+        // The destination only determines the tilt of the copter.
+        let dest = match copter_direction {
+            Side::Left => position - Vec2::X,
+            Side::Right => position + Vec2::X,
+        };
+        self.roflcopter_state = RoflcopterState::Flying { position, dest };
+    }
+}
+
+impl RoflcopterAnimation {
     /// Draw the helicopter, depending on the current state.
     pub fn draw_roflcopter(&self, state: &State) {
-        match self.roflcopter_state {
+        // The copter blinks, while it's invulnerable.
+        let invulnerable = self.simulation.invulnerable;
+        if !invulnerable.is_zero() && (invulnerable.as_millis() / 100) % 2 == 1 {
+            return;
+        }
+
+        let rotor_direction = &self.simulation.rotor_direction;
+        match self.simulation.roflcopter_state {
             RoflcopterState::Flying {
                 ref position,
                 ref dest,
@@ -106,7 +189,7 @@ impl RoflcopterAnimation {
                 draw_roflcopter(
                     &self.textures,
                     &copter_direction,
-                    &self.rotor_direction,
+                    rotor_direction,
                     position.x,
                     position.y,
                     angle,
@@ -127,14 +210,7 @@ impl RoflcopterAnimation {
                 let x = position.x;
                 let y = position.y + offset * state.font_dimensions.height;
 
-                draw_roflcopter(
-                    &self.textures,
-                    copter_direction,
-                    &self.rotor_direction,
-                    x,
-                    y,
-                    0.0,
-                );
+                draw_roflcopter(&self.textures, copter_direction, rotor_direction, x, y, 0.0);
             }
         }
     }
@@ -0,0 +1,130 @@
+use log::warn;
+use macroquad::prelude::*;
+
+use super::draw::{draw_hud_text, hud_text_width};
+use super::highscores::{HighScores, MAX_ENTRIES, MAX_NAME_LENGTH};
+use super::{CopterSimulation, RoflcopterAnimation};
+use crate::config::CopterConfig;
+use crate::simulation::Step;
+use crate::state::State;
+
+/// Load the configured high score table.
+/// Broken files are ignored, as the game shouldn't stop working because of them.
+pub fn load_high_scores(config: &CopterConfig) -> HighScores {
+    let Some(path) = &config.high_scores else {
+        return HighScores::default();
+    };
+
+    HighScores::load(path).unwrap_or_else(|err| {
+        warn!("{err}");
+        HighScores::default()
+    })
+}
+
+impl CopterSimulation {
+    /// The points of each kill are multiplied by the current combo.
+    pub fn multiplier(&self) -> usize {
+        self.combo.clamp(1, self.max_combo.max(1))
+    }
+
+    /// Award the points of a killed enemy.
+    /// Kills in quick succession increase the combo.
+    pub fn register_kill(&mut self, points: usize) {
+        self.combo += 1;
+        self.combo_timer = self.combo_timeout;
+        self.score += points * self.multiplier();
+    }
+
+    /// The combo breaks, if there hasn't been a kill for a while.
+    pub fn update_combo(&mut self, step: &Step) {
+        self.combo_timer = self.combo_timer.saturating_sub(step.delta);
+        if self.combo_timer.is_zero() {
+            self.combo = 0;
+        }
+    }
+
+    /// Type the name for a new high score.
+    /// Enter saves the score, an empty name discards it.
+    pub fn enter_name(&mut self, step: &Step) {
+        let Some(name) = self.name_entry.as_mut() else {
+            return;
+        };
+
+        if step.input.is_key_pressed(KeyCode::Backspace) {
+            name.pop();
+        }
+        for character in step.input.chars.iter() {
+            if character.is_alphanumeric() && name.chars().count() < MAX_NAME_LENGTH {
+                name.push(*character);
+            }
+        }
+
+        if !step.input.is_key_pressed(KeyCode::Enter) {
+            return;
+        }
+        let name = self.name_entry.take().unwrap_or_default();
+        if name.is_empty() {
+            return;
+        }
+
+        self.new_high_score = self.high_scores.insert(name, self.score);
+        if let Err(err) = self.high_scores.save() {
+            warn!("{err}");
+        }
+    }
+}
+
+impl RoflcopterAnimation {
+    /// Draw the score and the current combo in the top left corner.
+    pub fn draw_score(&self, state: &State) {
+        let simulation = &self.simulation;
+        let margin = state.font_dimensions.width;
+        let mut text = format!("SCORE {}", simulation.score);
+        if simulation.combo > 1 {
+            text.push_str(&format!("  COMBO x{}", simulation.multiplier()));
+        }
+
+        draw_hud_text(state, &text, Vec2::new(margin, margin), WHITE);
+    }
+
+    /// Draw the high score table centered below the given height.
+    /// The name that's currently typed is shown in place of its future entry.
+    pub fn draw_high_scores(&self, state: &State, top: f32) {
+        let simulation = &self.simulation;
+        let mut entries: Vec<(String, usize, bool)> = simulation
+            .high_scores
+            .scores
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let highlight = simulation.new_high_score == Some(index);
+                (entry.name.clone(), entry.score, highlight)
+            })
+            .collect();
+        if let Some(name) = &simulation.name_entry {
+            let index = entries.partition_point(|(_, score, _)| *score >= simulation.score);
+            entries.insert(index, (format!("{name}_"), simulation.score, true));
+        }
+
+        let line_height = state.font_dimensions.height * 1.5;
+        for (index, (name, score, highlight)) in entries.iter().enumerate().take(MAX_ENTRIES) {
+            let text = format!(
+                "{:>2}. {name:<width$} {score:>8}",
+                index + 1,
+                width = MAX_NAME_LENGTH + 1
+            );
+            let color = if *highlight {
+                state.palette.colors[0]
+            } else {
+                WHITE
+            };
+            let x = (state.window_width - hud_text_width(state, &text)) / 2.0;
+            draw_hud_text(
+                state,
+                &text,
+                Vec2::new(x, top + line_height * index as f32),
+                color,
+            );
+        }
+    }
+}
@@ -2,79 +2,119 @@ use std::{f32::consts::PI, time::Duration};
 
 use macroquad::prelude::*;
 
-use super::{RoflcopterAnimation, RoflcopterState};
+use super::effects::DEBRIS_COUNT;
+use super::enemy::Enemy;
+use super::weapon::Weapon;
+use super::{CopterSimulation, RoflcopterAnimation, RoflcopterState};
 use crate::helper::*;
-use crate::state::State;
+use crate::simulation::Step;
 
 #[derive(Debug, Clone)]
 pub struct Shot {
+    /// The weapon, that fired this shot.
+    pub weapon: Weapon,
     pub position: Vec2,
-    pub direction: Side,
     pub angle: f32,
+    pub damage: usize,
+    /// The distance in pixels, after which a bomb explodes.
+    pub range: Option<f32>,
 }
 
-impl RoflcopterAnimation {
+impl Shot {
+    /// A plain blaster shot.
+    pub fn new(position: Vec2, angle: f32) -> Shot {
+        Shot {
+            weapon: Weapon::Blaster,
+            position,
+            angle,
+            damage: 1,
+            range: None,
+        }
+    }
+
+    /// The box around the shot's art, which is turned into its flying direction.
+    pub fn obb(&self, step: &Step) -> Obb {
+        let size = text_size(self.weapon.art(), step.glyph);
+        Obb::from_texture(self.position, size, self.angle)
+    }
+}
+
+impl CopterSimulation {
     /// Tick all shots and spawn new ones, if the mouse is down.
-    pub fn update_shots(&mut self, state: &State) {
+    pub fn update_shots(&mut self, step: &Step) {
+        self.update_weapon(step);
+
         // Move all shots and check if they hit something or are off screen.
-        let mut shots_to_remove = Vec::new();
-        let mut enemies_to_remove = Vec::new();
-        for (shot_index, shot) in self.shots.iter_mut().enumerate() {
-            // Update the shot's positoin
-            let speed = state.window_width / 60.0;
+        let (grid, enemy_boxes) = self.enemy_grid(step);
+        let mut shots = std::mem::take(&mut self.shots);
+        shots.retain_mut(|shot| {
+            if shot.weapon == Weapon::Homing {
+                self.steer_missile(step, shot);
+            }
+
+            // Update the shot's position
+            let speed = shot.weapon.speed() * step.window_width;
             let direction = Vec2::new(shot.angle.cos(), shot.angle.sin());
-            let distance = direction * speed;
-            shot.position += distance;
-
-            // Check enemy collision
-            for (enemy_index, enemy) in self.enemies.iter_mut().enumerate() {
-                let distance = shot.position - enemy.position;
-                if distance.length() < 50.0 {
-                    shots_to_remove.push(shot_index);
-
-                    enemy.health -= 1;
-                    if enemy.health == 0 {
-                        enemies_to_remove.push(enemy_index);
-                    }
+            let distance = speed * step.delta_secs();
+            shot.position += direction * distance;
+
+            // Bombs explode once they reached their target.
+            if let Some(ref mut range) = shot.range {
+                *range -= distance;
+                if *range <= 0.0 {
+                    self.explode(step, shot.position, shot.damage);
+                    return false;
                 }
             }
 
-            // Check if the shot left the image and can be removed
-            let text_width = self.textures.shot.width();
-            if shot.position.x > state.window_width
-                || shot.position.x < 0.0 - text_width
-                || shot.position.y > state.window_height + text_width
-                || shot.position.y < 0.0 - text_width
-            {
-                shots_to_remove.push(shot_index);
+            // Check enemy collision with the actual shape of the shot and the enemies.
+            let obb = shot.obb(step);
+            let hit = grid.query(obb.bounds()).into_iter().find(|index| {
+                self.enemies[*index].health > 0 && enemy_boxes[*index].intersects(&obb)
+            });
+            if let Some(index) = hit {
+                let enemy = &mut self.enemies[index];
+                enemy.health = enemy.health.saturating_sub(shot.damage);
+                if shot.weapon == Weapon::Bomb {
+                    self.explode(step, shot.position, shot.damage);
+                }
+                return false;
             }
-        }
 
-        shots_to_remove.sort();
-        shots_to_remove.dedup();
-        shots_to_remove.reverse();
-        for index in shots_to_remove {
-            self.shots.remove(index);
-        }
+            // Check if the shot left the image and can be removed
+            let text_width = self.shot_size.x;
+            !(shot.position.x > step.window_width
+                || shot.position.x < 0.0 - text_width
+                || shot.position.y > step.window_height + text_width
+                || shot.position.y < 0.0 - text_width)
+        });
+        // Shots, that have been fired in the meantime, are kept as well.
+        shots.append(&mut self.shots);
+        self.shots = shots;
 
-        enemies_to_remove.sort();
-        enemies_to_remove.dedup();
-        enemies_to_remove.reverse();
-        for index in enemies_to_remove {
-            self.enemies.remove(index);
-        }
+        self.remove_dead_enemies(step);
 
         // Check if we want to spawn new shots.
         if self.shot_timer.as_micros() == 0 {
-            let copter_position = self.get_copter_position();
-            if is_mouse_button_down(MouseButton::Left) {
-                self.spawn_shot(Vec2::new(state.mouse_position.0, state.mouse_position.1))
+            let copter_position = self.copter_position();
+            if step.input.mouse_left_down {
+                self.fire(step, step.input.mouse_position)
+            } else if !self.autopilot {
+                // Manual pilots fire straight ahead.
+                if step.input.is_key_down(KeyCode::Space) {
+                    let ahead = match self.copter_direction() {
+                        Side::Left => -Vec2::X,
+                        Side::Right => Vec2::X,
+                    };
+                    let middle = copter_position + self.copter_size / 2.0;
+                    self.fire(step, middle + ahead * step.window_width);
+                }
             } else {
                 // Get the closest enemy.
                 let mut best_position: Option<(f32, Vec2)> = None;
                 for enemy in self.enemies.iter() {
                     // Don't shoot at enemies, that cannot be seen yet.
-                    if outside_screen(state, enemy.position).is_some() {
+                    if outside_screen(step, enemy.position).is_some() {
                         continue;
                     }
 
@@ -88,41 +128,42 @@ impl RoflcopterAnimation {
                     }
                 }
 
-                // Fire a shot, if we found an enemy.
+                // Fire a shot, if we found an enemy. Otherwise attack the boss.
                 if let Some((_, position)) = best_position {
-                    self.spawn_shot(position);
+                    self.fire(step, position);
+                } else if let Some(position) = self.boss_target(step) {
+                    self.fire(step, position);
                 }
             }
         }
 
         // Tick the shot timer
-        self.shot_timer = self.shot_timer.checked_add(delta_duration()).unwrap();
-        if self.shot_timer > self.shot_timeout {
+        self.shot_timer = self.shot_timer.checked_add(step.delta).unwrap();
+        if self.shot_timer > self.weapon.cooldown(self.shot_timeout) {
             self.shot_timer = Duration::from_secs(0);
         }
     }
 
-    pub fn draw_shots(&self) {
-        for shot in self.shots.iter() {
-            draw_texture_ex(
-                self.textures.shot,
-                shot.position.x,
-                shot.position.y,
-                Color::from_rgba(255, 255, 255, 255),
-                DrawTextureParams {
-                    rotation: shot.angle,
-                    flip_y: true,
-                    ..Default::default()
-                },
-            )
+    /// Remove killed enemies, award their points and maybe drop a power-up.
+    /// They go out with a bang.
+    pub fn remove_dead_enemies(&mut self, step: &Step) {
+        let (dead, alive): (Vec<Enemy>, Vec<Enemy>) = std::mem::take(&mut self.enemies)
+            .into_iter()
+            .partition(|enemy| enemy.health == 0);
+        self.enemies = alive;
+
+        for enemy in dead {
+            self.register_kill(self.script.enemies[enemy.kind].score);
+            self.drop_power_up(step, enemy.position);
+            self.explode_debris(step, enemy.position, DEBRIS_COUNT);
         }
     }
 
-    /// Spawn a new shot depending on the current position and copter state.
-    pub fn spawn_shot(&mut self, dest: Vec2) {
-        let dimensions = self.textures.copter_dimensions();
+    /// The point, where new shots leave the copter, depending on its state.
+    pub fn muzzle(&self, dest: Vec2) -> Vec2 {
+        let dimensions = self.copter_size;
         // Calculate the middle of the copter.
-        let middle = middle_texture_position(self.get_copter_position(), self.textures.texture());
+        let middle = self.copter_position() + dimensions / 2.0;
 
         match self.roflcopter_state {
             RoflcopterState::Flying { .. } => {
@@ -141,15 +182,7 @@ impl RoflcopterAnimation {
                     }
                 };
 
-                let position = middle + shot_offset;
-                let distance = dest - position;
-                let angle = vec2_to_radian(distance);
-
-                self.shots.push(Shot {
-                    position,
-                    direction,
-                    angle,
-                })
+                middle + shot_offset
             }
             RoflcopterState::Hovering {
                 ref copter_direction,
@@ -161,16 +194,26 @@ impl RoflcopterAnimation {
                     Side::Right => Vec2::new(dimensions.x / 2.0, 0.0),
                 };
 
-                let position = middle + shot_offset;
-                let distance = dest - position;
-                let angle = vec2_to_radian(distance);
-
-                self.shots.push(Shot {
-                    position,
-                    direction: copter_direction.clone(),
-                    angle,
-                })
+                middle + shot_offset
             }
         }
     }
 }
+
+impl RoflcopterAnimation {
+    pub fn draw_shots(&self) {
+        for shot in self.simulation.shots.iter() {
+            draw_texture_ex(
+                self.textures.shots[shot.weapon.index()],
+                shot.position.x,
+                shot.position.y,
+                Color::from_rgba(255, 255, 255, 255),
+                DrawTextureParams {
+                    rotation: shot.angle,
+                    flip_y: true,
+                    ..Default::default()
+                },
+            )
+        }
+    }
+}
@@ -0,0 +1,1056 @@
+use std::time::Duration;
+
+use macroquad::prelude::{KeyCode, Vec2};
+
+use super::{
+    boss::{Boss, Projectile},
+    effects::{debris_emitter, particle_system, smoke_emitter},
+    enemy::Enemy,
+    highscores::HighScores,
+    shot::Shot,
+    waves::WaveScript,
+    weapon::{Beam, Explosion, PowerUp, Weapon},
+    RoflcopterState,
+};
+use crate::{config::CopterConfig, helper::*, simulation::Step};
+
+/// Switches between the autopilot and manual flight.
+pub const AUTOPILOT_KEY: KeyCode = KeyCode::M;
+
+/// All keys that are used during manual flight.
+pub const MANUAL_KEYS: [KeyCode; 10] = [
+    KeyCode::W,
+    KeyCode::A,
+    KeyCode::S,
+    KeyCode::D,
+    KeyCode::Up,
+    KeyCode::Left,
+    KeyCode::Down,
+    KeyCode::Right,
+    KeyCode::Space,
+    AUTOPILOT_KEY,
+];
+
+/// The game logic of the copter animation.
+#[derive(Debug, Clone)]
+pub struct CopterSimulation {
+    pub roflcopter_state: RoflcopterState,
+    /// Whether the copter flies on its own. Otherwise it's steered with the keyboard.
+    pub autopilot: bool,
+    /// The current speed during manual flight.
+    pub(super) velocity: Vec2,
+
+    pub health: usize,
+    pub(super) max_health: usize,
+    pub lives: usize,
+    pub(super) max_lives: usize,
+    /// The time the copter still can't be hit.
+    pub(super) invulnerable: Duration,
+    pub(super) invulnerability: Duration,
+    /// How long the game has been over, if it is.
+    pub game_over: Option<Duration>,
+    pub(super) respawn_timeout: Duration,
+
+    pub score: usize,
+    /// The amount of kills in quick succession.
+    pub(super) combo: usize,
+    /// The time left to extend the combo.
+    pub(super) combo_timer: Duration,
+    pub(super) combo_timeout: Duration,
+    pub(super) max_combo: usize,
+    pub(super) high_scores: HighScores,
+    /// The name that's being typed for a new high score.
+    pub(super) name_entry: Option<String>,
+    /// The position of the high score, that has just been entered.
+    pub(super) new_high_score: Option<usize>,
+
+    pub(super) rotor_direction: Side,
+    pub(super) rotor_duration: Duration,
+    pub(super) rotor_timer: Duration,
+
+    pub(super) shot_timeout: Duration,
+    pub(super) shot_timer: Duration,
+    pub(super) shots: Vec<Shot>,
+    pub(super) weapon: Weapon,
+    pub(super) weapon_level: usize,
+    /// The time left, until the copter falls back to its blaster.
+    pub(super) weapon_timer: Duration,
+    pub(super) weapon_duration: Duration,
+    pub(super) power_ups: Vec<PowerUp>,
+    pub(super) power_up_chance: f32,
+    pub(super) power_up_lifetime: Duration,
+    pub(super) beams: Vec<Beam>,
+    pub(super) explosions: Vec<Explosion>,
+
+    pub(super) particles: ParticleSystem,
+    /// Enemies explode into debris.
+    pub(super) debris: Emitter,
+    /// Damaged copters smoke.
+    pub(super) smoke: Emitter,
+
+    pub(super) enemies: Vec<Enemy>,
+    pub(super) spawn_enemies: bool,
+    pub(super) script: WaveScript,
+    /// The next wave of the script.
+    pub(super) wave_index: usize,
+    /// How often the script has been completed, which makes the waves harder.
+    pub(super) round: usize,
+    pub(super) enemy_wave_timer: Duration,
+
+    pub(super) boss: Option<Boss>,
+    pub(super) projectiles: Vec<Projectile>,
+    /// The time since the last boss has been defeated.
+    pub(super) victory: Option<Duration>,
+    /// Whether the animation ends, once a boss has been defeated.
+    pub(super) end_after_boss: bool,
+
+    /// The size of the copter's texture, which is needed to aim and to stay on screen.
+    pub(super) copter_size: Vec2,
+    /// The size of the shot's texture, which is needed to detect shots that left the screen.
+    pub(super) shot_size: Vec2,
+}
+
+impl CopterSimulation {
+    pub fn new(
+        config: &CopterConfig,
+        script: WaveScript,
+        position: Vec2,
+        copter_size: Vec2,
+        shot_size: Vec2,
+    ) -> CopterSimulation {
+        CopterSimulation {
+            roflcopter_state: RoflcopterState::Flying {
+                position,
+                dest: Vec2::new(100.0, 100.0),
+            },
+            autopilot: true,
+            velocity: Vec2::ZERO,
+
+            health: config.health.max(1),
+            max_health: config.health.max(1),
+            lives: config.lives.max(1),
+            max_lives: config.lives.max(1),
+            invulnerable: Duration::from_secs(0),
+            invulnerability: config.invulnerability,
+            game_over: None,
+            respawn_timeout: config.respawn_timeout,
+
+            score: 0,
+            combo: 0,
+            combo_timer: Duration::from_secs(0),
+            combo_timeout: config.combo_timeout,
+            max_combo: config.max_combo,
+            high_scores: HighScores::default(),
+            name_entry: None,
+            new_high_score: None,
+
+            rotor_direction: Side::Left,
+            rotor_duration: Duration::from_millis(200),
+            rotor_timer: Duration::from_secs(0),
+
+            shot_timeout: config.shot_timeout,
+            shot_timer: Duration::from_secs(0),
+            shots: vec![],
+            weapon: Weapon::Blaster,
+            weapon_level: 1,
+            weapon_timer: Duration::from_secs(0),
+            weapon_duration: config.weapon_duration,
+            power_ups: vec![],
+            power_up_chance: config.power_up_chance,
+            power_up_lifetime: config.power_up_lifetime,
+            beams: vec![],
+            explosions: vec![],
+
+            particles: particle_system(),
+            debris: debris_emitter(),
+            smoke: smoke_emitter(),
+
+            enemies: vec![],
+            spawn_enemies: true,
+            script,
+            wave_index: 0,
+            round: 0,
+            enemy_wave_timer: Duration::from_secs(0),
+
+            boss: None,
+            projectiles: vec![],
+            victory: None,
+            end_after_boss: config.end_after_boss,
+
+            copter_size,
+            shot_size,
+        }
+    }
+
+    /// Advance the simulation by a single step.
+    pub fn step(&mut self, step: &Step) {
+        // M is part of the name, while a name is typed.
+        if step.input.is_key_pressed(AUTOPILOT_KEY) && self.name_entry.is_none() {
+            self.toggle_autopilot();
+        }
+        if self.game_over.is_some() {
+            self.update_game_over(step);
+            return;
+        }
+        // During manual flight, the mouse is only used to aim.
+        if self.autopilot {
+            self.follow_mouse(step);
+        }
+
+        self.update_combo(step);
+        self.update_shots(step);
+        self.update_boss(step);
+        self.spawn_enemies(step);
+        self.update_enemies(step);
+
+        self.update_roflcopter(step);
+        self.update_power_ups(step);
+        self.check_enemy_collisions(step);
+        self.update_particles(step);
+    }
+
+    /// The textures have been regenerated with a different font size.
+    pub fn resize(&mut self, copter_size: Vec2, shot_size: Vec2) {
+        self.copter_size = copter_size;
+        self.shot_size = shot_size;
+    }
+
+    pub fn copter_position(&self) -> Vec2 {
+        match self.roflcopter_state {
+            RoflcopterState::Flying { position, .. } => position,
+            RoflcopterState::Hovering { position, .. } => position,
+        }
+    }
+
+    /// The middle of the copter, which is where enemies are heading to.
+    pub fn copter_middle(&self) -> Vec2 {
+        self.copter_position() + self.copter_size / 2.0
+    }
+
+    /// The direction the copter is facing.
+    pub fn copter_direction(&self) -> Side {
+        match self.roflcopter_state {
+            RoflcopterState::Flying { position, dest } => side(&position, &dest),
+            RoflcopterState::Hovering {
+                copter_direction, ..
+            } => copter_direction,
+        }
+    }
+
+    /// Switch between the autopilot and manual flight.
+    /// The copter starts hovering at its current position either way.
+    pub fn toggle_autopilot(&mut self) {
+        self.autopilot = !self.autopilot;
+        self.velocity = Vec2::ZERO;
+        // Without a duration, the autopilot picks a new destination right away.
+        self.roflcopter_state = RoflcopterState::Hovering {
+            duration: Duration::from_secs(0),
+            timer: Duration::from_secs(0),
+            position: self.copter_position(),
+            copter_direction: self.copter_direction(),
+        };
+    }
+
+    /// The copter follows the mouse, whenever it's moved.
+    fn follow_mouse(&mut self, step: &Step) {
+        if !step.input.mouse_moved {
+            return;
+        }
+
+        let mouse = step.input.mouse_position;
+        match self.roflcopter_state {
+            RoflcopterState::Hovering { position, .. } => {
+                self.roflcopter_state = RoflcopterState::Flying {
+                    position,
+                    dest: mouse,
+                };
+            }
+            RoflcopterState::Flying {
+                ref mut dest,
+                ref position,
+                ..
+            } => {
+                let dimensions = self.copter_size;
+                let new_dest = match side(position, dest) {
+                    Side::Left => Vec2::new(mouse.x, mouse.y - dimensions.y / 2.0),
+                    Side::Right => Vec2::new(mouse.x - dimensions.x, mouse.y - dimensions.y / 2.0),
+                };
+                *dest = new_dest;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::rand::RandGenerator;
+
+    use super::*;
+    use crate::animations::copter::boss::VICTORY_DURATION;
+    use crate::animations::copter::waves::Movement;
+    use crate::input::Input;
+
+    const WIDTH: f32 = 1280.0;
+    const HEIGHT: f32 = 720.0;
+
+    fn simulation() -> CopterSimulation {
+        let config = CopterConfig::default();
+        let mut simulation = CopterSimulation::new(
+            &config,
+            WaveScript::classic(&config),
+            Vec2::new(100.0, 600.0),
+            Vec2::new(200.0, 80.0),
+            Vec2::new(20.0, 12.0),
+        );
+        simulation.spawn_enemies = false;
+        simulation
+    }
+
+    fn step<'a>(input: &'a Input, rng: &'a RandGenerator) -> Step<'a> {
+        Step {
+            input,
+            delta: Duration::from_millis(10),
+            elapsed: Duration::from_secs(1),
+            window_width: WIDTH,
+            window_height: HEIGHT,
+            glyph: Vec2::new(10.0, 20.0),
+            rng,
+        }
+    }
+
+    fn enemy(position: Vec2, health: usize) -> Enemy {
+        Enemy::new(0, position, 50.0, health)
+    }
+
+    #[test]
+    fn shot_damages_enemy() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation();
+        simulation.enemies.push(enemy(Vec2::new(600.0, 300.0), 3));
+        simulation
+            .shots
+            .push(Shot::new(Vec2::new(580.0, 300.0), 0.0));
+        // Don't fire any new shots during this step.
+        simulation.shot_timer = Duration::from_millis(1);
+
+        simulation.step(&step(&input, &rng));
+
+        assert!(simulation.shots.is_empty());
+        assert_eq!(simulation.enemies.len(), 1);
+        assert_eq!(simulation.enemies[0].health, 2);
+    }
+
+    #[test]
+    fn shot_kills_enemy_without_health() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation();
+        simulation.enemies.push(enemy(Vec2::new(600.0, 300.0), 1));
+        simulation.enemies.push(enemy(Vec2::new(900.0, 100.0), 1));
+        simulation
+            .shots
+            .push(Shot::new(Vec2::new(580.0, 300.0), 0.0));
+        simulation.shot_timer = Duration::from_millis(1);
+
+        simulation.step(&step(&input, &rng));
+
+        assert!(simulation.shots.is_empty());
+        assert_eq!(simulation.enemies.len(), 1);
+        assert_eq!(simulation.enemies[0].health, 1);
+    }
+
+    #[test]
+    fn shots_leaving_the_screen_are_removed() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation();
+        simulation
+            .shots
+            .push(Shot::new(Vec2::new(WIDTH - 1.0, 300.0), 0.0));
+        simulation
+            .shots
+            .push(Shot::new(Vec2::new(400.0, 300.0), 0.0));
+        simulation.shot_timer = Duration::from_millis(1);
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.shots.len(), 1);
+        assert!(simulation.shots[0].position.x > 400.0);
+    }
+
+    #[test]
+    fn copter_aims_at_closest_visible_enemy() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation();
+        // The closest enemy is still outside of the screen and must be ignored.
+        simulation.enemies.push(enemy(Vec2::new(-50.0, 650.0), 3));
+        simulation.enemies.push(enemy(Vec2::new(1000.0, 640.0), 3));
+        simulation.enemies.push(enemy(Vec2::new(1200.0, 100.0), 3));
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.shots.len(), 1);
+        let target = Vec2::new(1000.0, 640.0);
+        let direction = Vec2::new(
+            simulation.shots[0].angle.cos(),
+            simulation.shots[0].angle.sin(),
+        );
+        let expected = (target - simulation.shots[0].position).normalize();
+        assert!(direction.dot(expected) > 0.99);
+    }
+
+    #[test]
+    fn mouse_click_fires_at_mouse() {
+        let rng = RandGenerator::new();
+        let input = Input {
+            mouse_position: Vec2::new(640.0, 0.0),
+            mouse_moved: false,
+            mouse_left_down: true,
+            ..Default::default()
+        };
+        let mut simulation = simulation();
+
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.shots.len(), 1);
+
+        // The next shot is only fired after the timeout.
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.shots.len(), 1);
+    }
+
+    #[test]
+    fn waves_spawn_outside_of_the_screen() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        rng.srand(42);
+        let mut simulation = simulation();
+        simulation.spawn_enemies = true;
+        simulation.enemy_wave_timer = simulation.script.waves[0].delay;
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(
+            simulation.enemies.len(),
+            simulation.script.waves[0].groups[0].count
+        );
+        // All enemies are spawned around a cluster center that's just outside of the screen.
+        let center = simulation
+            .enemies
+            .iter()
+            .fold(Vec2::ZERO, |sum, enemy| sum + enemy.position)
+            / simulation.enemies.len() as f32;
+        assert!(
+            center.x < WIDTH * 0.1
+                || center.x > WIDTH * 0.9
+                || center.y < HEIGHT * 0.1
+                || center.y > HEIGHT * 0.9
+        );
+    }
+
+    #[test]
+    fn enemies_move_towards_the_copter() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation();
+        simulation.enemies.push(enemy(Vec2::new(1000.0, 600.0), 3));
+        simulation.shot_timer = Duration::from_millis(1);
+
+        let copter = simulation.copter_position();
+        let before = (simulation.enemies[0].position - copter).length();
+        simulation.step(&step(&input, &rng));
+        let after = (simulation.enemies[0].position - copter).length();
+
+        assert!(after < before);
+    }
+
+    fn keys(keys: &[KeyCode]) -> Input {
+        Input {
+            keys_down: keys.to_vec(),
+            keys_pressed: keys.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn autopilot_can_be_toggled() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        assert!(simulation.autopilot);
+
+        simulation.step(&step(&keys(&[AUTOPILOT_KEY]), &rng));
+        assert!(!simulation.autopilot);
+
+        // Manual copters stay where they are, until they're steered.
+        let position = simulation.copter_position();
+        simulation.step(&step(&Input::default(), &rng));
+        assert_eq!(simulation.copter_position(), position);
+
+        simulation.step(&step(&keys(&[AUTOPILOT_KEY]), &rng));
+        assert!(simulation.autopilot);
+    }
+
+    #[test]
+    fn manual_flight_has_inertia() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.toggle_autopilot();
+        let start = simulation.copter_position();
+
+        for _ in 0..20 {
+            simulation.step(&step(&keys(&[KeyCode::A]), &rng));
+        }
+        let steered = simulation.copter_position();
+        assert!(steered.x < start.x);
+        assert_eq!(steered.y, start.y);
+        assert_eq!(simulation.copter_direction(), Side::Left);
+
+        // The copter keeps drifting for a while, after the key has been released.
+        simulation.step(&step(&Input::default(), &rng));
+        assert!(simulation.copter_position().x < steered.x);
+        assert!(matches!(
+            simulation.roflcopter_state,
+            RoflcopterState::Flying { .. }
+        ));
+
+        // And eventually starts to hover.
+        for _ in 0..500 {
+            simulation.step(&step(&Input::default(), &rng));
+        }
+        assert!(matches!(
+            simulation.roflcopter_state,
+            RoflcopterState::Hovering { .. }
+        ));
+        assert_eq!(simulation.copter_direction(), Side::Left);
+    }
+
+    #[test]
+    fn manual_flight_stays_on_screen() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.toggle_autopilot();
+
+        for _ in 0..500 {
+            simulation.step(&step(&keys(&[KeyCode::Right, KeyCode::Down]), &rng));
+        }
+
+        let position = simulation.copter_position();
+        assert_eq!(position.x, WIDTH - simulation.copter_size.x);
+        assert_eq!(position.y, HEIGHT - simulation.copter_size.y);
+    }
+
+    #[test]
+    fn space_fires_ahead() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.toggle_autopilot();
+        // Enemies are ignored during manual flight.
+        simulation.enemies.push(enemy(Vec2::new(1000.0, 640.0), 3));
+
+        simulation.step(&step(&Input::default(), &rng));
+        assert!(simulation.shots.is_empty());
+
+        simulation.step(&step(&keys(&[KeyCode::Left]), &rng));
+        simulation.shot_timer = Duration::from_secs(0);
+        simulation.step(&step(&keys(&[KeyCode::Space]), &rng));
+
+        assert_eq!(simulation.shots.len(), 1);
+        // The shot flies to the left, which is where the copter is facing.
+        assert!(simulation.shots[0].angle.cos() < -0.99);
+    }
+
+    /// Crash a single enemy into the copter.
+    fn crash(simulation: &mut CopterSimulation, rng: &RandGenerator) {
+        let middle = simulation.copter_middle();
+        simulation.enemies.push(enemy(middle, 3));
+        simulation.step(&step(&Input::default(), rng));
+    }
+
+    #[test]
+    fn enemies_damage_the_copter() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+
+        crash(&mut simulation, &rng);
+
+        assert!(simulation.enemies.is_empty());
+        assert_eq!(simulation.health, 2);
+        assert_eq!(simulation.lives, 3);
+        assert!(simulation.is_invulnerable());
+    }
+
+    #[test]
+    fn invulnerable_copters_take_no_damage() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+
+        crash(&mut simulation, &rng);
+        crash(&mut simulation, &rng);
+        assert!(simulation.enemies.is_empty());
+        assert_eq!(simulation.health, 2);
+
+        simulation.invulnerable = Duration::from_millis(5);
+        crash(&mut simulation, &rng);
+        assert_eq!(simulation.health, 1);
+    }
+
+    #[test]
+    fn losing_all_lives_ends_the_game() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+
+        for _ in 0..9 {
+            assert!(simulation.game_over.is_none());
+            simulation.invulnerable = Duration::from_secs(0);
+            crash(&mut simulation, &rng);
+        }
+
+        assert_eq!(simulation.lives, 0);
+        assert_eq!(simulation.game_over, Some(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn autopilot_respawns_after_timeout() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation();
+        simulation.lives = 0;
+        simulation.game_over = Some(Duration::from_millis(4985));
+
+        simulation.step(&step(&input, &rng));
+        assert!(simulation.game_over.is_some());
+        simulation.step(&step(&input, &rng));
+
+        assert!(simulation.game_over.is_none());
+        assert_eq!(simulation.lives, 3);
+        assert_eq!(simulation.health, 3);
+    }
+
+    #[test]
+    fn manual_flight_restarts_with_space() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.toggle_autopilot();
+        simulation.lives = 0;
+        simulation.game_over = Some(Duration::from_secs(60));
+
+        simulation.step(&step(&Input::default(), &rng));
+        assert!(simulation.game_over.is_some());
+
+        simulation.step(&step(&keys(&[KeyCode::Space]), &rng));
+        assert!(simulation.game_over.is_none());
+        assert!(simulation.shots.is_empty());
+    }
+
+    /// Kill a single enemy with a shot.
+    fn kill(simulation: &mut CopterSimulation, rng: &RandGenerator) {
+        simulation.enemies.push(enemy(Vec2::new(600.0, 300.0), 1));
+        simulation
+            .shots
+            .push(Shot::new(Vec2::new(590.0, 300.0), 0.0));
+        simulation.shot_timer = Duration::from_millis(1);
+        simulation.step(&step(&Input::default(), rng));
+    }
+
+    #[test]
+    fn quick_kills_build_a_combo() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+
+        kill(&mut simulation, &rng);
+        kill(&mut simulation, &rng);
+        kill(&mut simulation, &rng);
+        assert_eq!(simulation.combo, 3);
+        assert_eq!(simulation.score, 100 + 200 + 300);
+
+        // The combo breaks after a while without kills.
+        simulation.combo_timer = Duration::from_millis(5);
+        simulation.step(&step(&Input::default(), &rng));
+        assert_eq!(simulation.combo, 0);
+
+        kill(&mut simulation, &rng);
+        assert_eq!(simulation.score, 700);
+    }
+
+    #[test]
+    fn combos_are_capped() {
+        let mut simulation = simulation();
+        simulation.combo = 20;
+
+        simulation.register_kill(100);
+
+        assert_eq!(simulation.multiplier(), 8);
+        assert_eq!(simulation.score, 800);
+    }
+
+    fn typed(text: &str, keys: &[KeyCode]) -> Input {
+        Input {
+            keys_pressed: keys.to_vec(),
+            chars: text.chars().collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn manual_pilots_enter_their_name() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.toggle_autopilot();
+        simulation.score = 500;
+        simulation.end_game();
+        assert_eq!(simulation.name_entry.as_deref(), Some(""));
+
+        // Neither M nor Space do anything while typing.
+        simulation.step(&step(&typed("Max ", &[KeyCode::M, KeyCode::Space]), &rng));
+        assert!(!simulation.autopilot);
+        simulation.step(&step(&typed("x", &[KeyCode::Backspace]), &rng));
+        assert_eq!(simulation.name_entry.as_deref(), Some("Max"));
+
+        simulation.step(&step(&typed("", &[KeyCode::Enter]), &rng));
+        assert!(simulation.name_entry.is_none());
+        assert!(simulation.game_over.is_some());
+        assert_eq!(simulation.new_high_score, Some(0));
+        assert_eq!(simulation.high_scores.scores[0].name, "Max");
+        assert_eq!(simulation.high_scores.scores[0].score, 500);
+
+        simulation.step(&step(&keys(&[KeyCode::Space]), &rng));
+        assert!(simulation.game_over.is_none());
+        assert_eq!(simulation.score, 0);
+    }
+
+    #[test]
+    fn autopilot_scores_are_not_recorded() {
+        let mut simulation = simulation();
+        simulation.score = 500;
+
+        simulation.end_game();
+
+        assert!(simulation.name_entry.is_none());
+    }
+
+    #[test]
+    fn scripts_start_over_with_harder_waves() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation();
+        simulation.spawn_enemies = true;
+        simulation.script.escalation.count = 3;
+        simulation.script.escalation.health = 1;
+        let wave_size = simulation.script.waves[0].groups[0].count;
+
+        simulation.enemy_wave_timer = simulation.script.waves[0].delay;
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.round, 1);
+        assert_eq!(simulation.enemies.len(), wave_size);
+
+        simulation.enemies.clear();
+        simulation.enemy_wave_timer = simulation.script.waves[0].delay;
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.round, 2);
+        assert_eq!(simulation.enemies.len(), wave_size + 3);
+        assert_eq!(simulation.enemies[0].max_health, 4);
+    }
+
+    #[test]
+    fn movement_patterns() {
+        let rng = RandGenerator::new();
+        let input = Input::default();
+        let step = Step {
+            delta: Duration::from_millis(250),
+            ..step(&input, &rng)
+        };
+        let target = Vec2::new(0.0, 0.0);
+
+        let mut straight = enemy(Vec2::new(100.0, 0.0), 1);
+        straight.advance(Movement::Straight, target, &step);
+        assert_eq!(straight.position, Vec2::new(87.5, 0.0));
+
+        // Zigzagging enemies leave the straight path, but still make progress.
+        let mut zigzag = enemy(Vec2::new(100.0, 0.0), 1);
+        let movement = Movement::Zigzag {
+            amplitude: 0.01,
+            frequency: 1.0,
+        };
+        zigzag.advance(movement, target, &step);
+        assert_eq!(zigzag.anchor, straight.position);
+        assert!((zigzag.position.y.abs() - WIDTH * 0.01).abs() < 0.01);
+
+        // Orbiting enemies circle the target and only slowly close in.
+        let mut orbit = enemy(Vec2::new(100.0, 0.0), 1);
+        let movement = Movement::Orbit {
+            radius: 0.5,
+            angular_speed: 1.0,
+        };
+        orbit.advance(movement, target, &step);
+        assert!((orbit.position.length() - 97.5).abs() < 0.01);
+        assert!(orbit.position.y > 0.0);
+
+        let mut formation = enemy(Vec2::new(100.0, 0.0), 1);
+        formation.offset = Vec2::new(-10.0, 10.0);
+        formation.advance(Movement::Formation { spacing: 0.01 }, target, &step);
+        // The formation turns with its flying direction.
+        assert!(formation.position.distance(Vec2::new(97.5, -10.0)) < 0.01);
+    }
+
+    /// A script with a single boss, that has two hit zones and switches its attack at half health.
+    fn boss_script() -> WaveScript {
+        let content = r#"
+            [bosses.blimp]
+            art = "[##]"
+            zones = [{ area = [0, 0, 2, 1], health = 2 }, { area = [2, 0, 2, 1], health = 2 }]
+            phases = [
+                { attacks = [{ pattern = "aimed", interval = 0.01 }] },
+                { health = 0.5, attacks = [{ pattern = "ring", count = 6, interval = 0.01 }] },
+            ]
+
+            [[waves]]
+            delay = 0
+            boss = "blimp"
+        "#;
+        WaveScript::parse(std::path::Path::new("waves.toml"), content).unwrap()
+    }
+
+    /// A simulation, whose boss already arrived on screen.
+    fn boss_fight(rng: &RandGenerator) -> CopterSimulation {
+        let mut simulation = simulation();
+        simulation.script = boss_script();
+        simulation.spawn_enemies = true;
+        simulation.shot_timer = Duration::from_millis(1);
+
+        let input = Input::default();
+        simulation.step(&step(&input, rng));
+        simulation.boss.as_mut().unwrap().position = Vec2::new(1000.0, 300.0);
+        simulation
+    }
+
+    /// Fire a shot right into the given point.
+    fn shoot_at(simulation: &mut CopterSimulation, position: Vec2, rng: &RandGenerator) {
+        simulation
+            .shots
+            .push(Shot::new(position - Vec2::new(5.0, 0.0), 0.0));
+        simulation.shot_timer = Duration::from_millis(1);
+        simulation.step(&step(&Input::default(), rng));
+    }
+
+    #[test]
+    fn bosses_pause_the_waves_and_attack() {
+        let rng = RandGenerator::new();
+        let mut simulation = boss_fight(&rng);
+        assert!(simulation.projectiles.is_empty());
+
+        simulation.step(&step(&Input::default(), &rng));
+
+        assert!(simulation.boss.is_some());
+        assert_eq!(simulation.projectiles.len(), 1);
+        assert_eq!(simulation.round, 1);
+        // The next round only starts, once the boss has been defeated.
+        simulation.step(&step(&Input::default(), &rng));
+        assert_eq!(simulation.round, 1);
+    }
+
+    #[test]
+    fn projectiles_damage_the_copter() {
+        let rng = RandGenerator::new();
+        let mut simulation = boss_fight(&rng);
+        simulation.projectiles.push(Projectile {
+            position: simulation.copter_middle(),
+            velocity: Vec2::ZERO,
+        });
+
+        simulation.step(&step(&Input::default(), &rng));
+
+        assert_eq!(simulation.health, 2);
+    }
+
+    #[test]
+    fn hit_zones_take_damage() {
+        let rng = RandGenerator::new();
+        let mut simulation = boss_fight(&rng);
+        let glyph = Vec2::new(10.0, 20.0);
+
+        // The shot hits the first zone.
+        shoot_at(&mut simulation, Vec2::new(1005.0, 310.0), &rng);
+        assert_eq!(simulation.boss.as_ref().unwrap().zones, vec![1, 2]);
+        assert!(simulation.shots.is_empty());
+
+        // The second row isn't part of any zone, so the shot is absorbed without damage.
+        shoot_at(
+            &mut simulation,
+            Vec2::new(1005.0, 300.0 + glyph.y * 1.5),
+            &rng,
+        );
+        assert_eq!(simulation.boss.as_ref().unwrap().zones, vec![1, 2]);
+        assert!(simulation.shots.is_empty());
+
+        // At half health, the boss switches to its next phase.
+        shoot_at(&mut simulation, Vec2::new(1005.0, 310.0), &rng);
+        simulation.projectiles.clear();
+        simulation.step(&step(&Input::default(), &rng));
+        assert_eq!(simulation.boss.as_ref().unwrap().phase, 1);
+        assert_eq!(simulation.projectiles.len(), 6);
+    }
+
+    #[test]
+    fn defeated_bosses_end_the_animation() {
+        let rng = RandGenerator::new();
+        let mut simulation = boss_fight(&rng);
+        let score = simulation.score;
+
+        for _ in 0..2 {
+            shoot_at(&mut simulation, Vec2::new(1005.0, 310.0), &rng);
+            shoot_at(&mut simulation, Vec2::new(1025.0, 310.0), &rng);
+        }
+
+        assert!(simulation.boss.is_none());
+        assert!(simulation.projectiles.is_empty());
+        assert_eq!(simulation.score, score + 5000);
+        assert!(!simulation.boss_defeated());
+
+        simulation.victory = Some(VICTORY_DURATION);
+        assert!(simulation.boss_defeated());
+    }
+
+    /// Fire the current weapon once at the given point, without moving the copter.
+    fn fire_at(simulation: &mut CopterSimulation, target: Vec2, rng: &RandGenerator) {
+        let input = Input {
+            mouse_position: target,
+            mouse_left_down: true,
+            ..Default::default()
+        };
+        simulation.autopilot = false;
+        simulation.shot_timer = Duration::from_secs(0);
+        simulation.step(&step(&input, rng));
+    }
+
+    #[test]
+    fn spread_shots_widen_with_each_level() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+
+        simulation.collect(Weapon::Spread);
+        fire_at(&mut simulation, Vec2::new(1000.0, 300.0), &rng);
+        assert_eq!(simulation.shots.len(), 3);
+
+        simulation.shots.clear();
+        simulation.collect(Weapon::Spread);
+        fire_at(&mut simulation, Vec2::new(1000.0, 300.0), &rng);
+        assert_eq!(simulation.weapon_level, 2);
+        assert_eq!(simulation.shots.len(), 5);
+    }
+
+    #[test]
+    fn weapons_run_out() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+
+        simulation.collect(Weapon::Laser);
+        simulation.collect(Weapon::Bomb);
+        assert_eq!(simulation.weapon, Weapon::Bomb);
+        assert_eq!(simulation.weapon_level, 1);
+
+        simulation.weapon_timer = Duration::from_millis(5);
+        simulation.shot_timer = Duration::from_millis(1);
+        simulation.step(&step(&Input::default(), &rng));
+        assert_eq!(simulation.weapon, Weapon::Blaster);
+    }
+
+    #[test]
+    fn lasers_pierce_all_enemies() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        let muzzle = simulation.muzzle(Vec2::new(WIDTH, 300.0));
+        for x in [400.0, 700.0, 1000.0] {
+            simulation.enemies.push(enemy(Vec2::new(x, muzzle.y), 1));
+        }
+        simulation.enemies.push(enemy(Vec2::new(700.0, 100.0), 1));
+
+        simulation.collect(Weapon::Laser);
+        fire_at(&mut simulation, Vec2::new(WIDTH, muzzle.y), &rng);
+
+        assert_eq!(simulation.beams.len(), 1);
+        assert!(simulation.shots.is_empty());
+        // The enemies are removed, once the next step cleans up.
+        simulation.shot_timer = Duration::from_millis(1);
+        simulation.step(&step(&Input::default(), &rng));
+        assert_eq!(simulation.enemies.len(), 1);
+        assert_eq!(simulation.score, 100 + 200 + 300);
+    }
+
+    #[test]
+    fn bombs_damage_everything_around_them() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.enemies.push(enemy(Vec2::new(600.0, 300.0), 3));
+        simulation.enemies.push(enemy(Vec2::new(600.0, 380.0), 3));
+        simulation.enemies.push(enemy(Vec2::new(600.0, 600.0), 3));
+        let mut bomb = Shot::new(Vec2::new(590.0, 300.0), 0.0);
+        bomb.weapon = Weapon::Bomb;
+        bomb.damage = 2;
+        simulation.shots.push(bomb);
+        simulation.shot_timer = Duration::from_millis(1);
+
+        simulation.step(&step(&Input::default(), &rng));
+
+        let health: Vec<usize> = simulation
+            .enemies
+            .iter()
+            .map(|enemy| enemy.health)
+            .collect();
+        // The hit enemy takes damage from both the impact and the blast.
+        assert_eq!(health, vec![1, 3]);
+        assert_eq!(simulation.explosions.len(), 1);
+    }
+
+    #[test]
+    fn missiles_follow_their_target() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.enemies.push(enemy(Vec2::new(600.0, 100.0), 3));
+        let mut missile = Shot::new(Vec2::new(300.0, 300.0), 0.0);
+        missile.weapon = Weapon::Homing;
+        simulation.shots.push(missile);
+        simulation.shot_timer = Duration::from_millis(1);
+
+        simulation.step(&step(&Input::default(), &rng));
+
+        // The missile turns upwards towards the enemy.
+        assert!(simulation.shots[0].angle < 0.0);
+    }
+
+    #[test]
+    fn power_ups_are_collected() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.power_up_chance = 1.0;
+        kill(&mut simulation, &rng);
+        assert_eq!(simulation.power_ups.len(), 1);
+
+        let weapon = simulation.power_ups[0].weapon;
+        simulation.power_ups[0].position = simulation.copter_middle();
+        simulation.step(&step(&Input::default(), &rng));
+
+        assert!(simulation.power_ups.is_empty());
+        assert_eq!(simulation.weapon, weapon);
+        assert_eq!(simulation.weapon_timer, simulation.weapon_duration);
+    }
+
+    #[test]
+    fn killed_enemies_explode() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+
+        kill(&mut simulation, &rng);
+
+        assert!(!simulation.particles.particles.is_empty());
+    }
+
+    #[test]
+    fn damaged_copters_smoke() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation();
+        simulation.shot_timer = Duration::from_millis(1);
+        for _ in 0..10 {
+            simulation.step(&step(&Input::default(), &rng));
+        }
+        assert!(simulation.particles.particles.is_empty());
+
+        simulation.health = 1;
+        for _ in 0..10 {
+            simulation.step(&step(&Input::default(), &rng));
+        }
+        assert!(!simulation.particles.particles.is_empty());
+    }
+}
@@ -0,0 +1,626 @@
+use std::{
+    collections::BTreeMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use macroquad::prelude::Vec2;
+use serde::Deserialize;
+
+use crate::config::{seconds, CopterConfig};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WaveError {
+    #[error("Couldn't read wave script {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Couldn't parse wave script {path:?}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Wave {wave} of {path:?} uses the unknown enemy '{enemy}'")]
+    UnknownEnemy {
+        path: PathBuf,
+        wave: usize,
+        enemy: String,
+    },
+    #[error("Wave script {path:?} doesn't contain any waves")]
+    Empty { path: PathBuf },
+    #[error("Boss '{boss}' of {path:?} doesn't have any hit zones")]
+    NoHitZones { path: PathBuf, boss: String },
+}
+
+/// How an enemy gets to the copter.
+/// Distances are relative to the window width.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "pattern", rename_all = "kebab-case")]
+pub enum Movement {
+    /// Fly straight at the copter.
+    #[default]
+    Straight,
+    /// Sway from side to side on the way to the copter.
+    Zigzag {
+        #[serde(default = "default_amplitude")]
+        amplitude: f32,
+        /// Full swings per second.
+        #[serde(default = "default_frequency")]
+        frequency: f32,
+    },
+    /// Circle around the copter, while slowly closing in.
+    Orbit {
+        #[serde(default = "default_radius")]
+        radius: f32,
+        /// Radians per second. Negative values circle counter-clockwise.
+        #[serde(default = "default_angular_speed")]
+        angular_speed: f32,
+    },
+    /// Fly in a V formation, which keeps its shape.
+    Formation {
+        #[serde(default = "default_spacing")]
+        spacing: f32,
+    },
+}
+
+impl Movement {
+    /// The place of the n-th enemy in a formation, with the x-axis pointing forward.
+    /// Everything but formations fly in a loose cluster instead.
+    pub fn formation_offset(&self, index: usize, width: f32) -> Option<Vec2> {
+        let Movement::Formation { spacing } = *self else {
+            return None;
+        };
+
+        let rank = index.div_ceil(2) as f32;
+        let side = if index % 2 == 1 { -1.0 } else { 1.0 };
+        Some(Vec2::new(-rank, side * rank) * spacing * width)
+    }
+}
+
+/// An enemy archetype of the wave script.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EnemyKind {
+    #[serde(skip)]
+    pub name: String,
+    pub art: String,
+    /// The speed per second, relative to the window width.
+    pub speed: f32,
+    pub health: usize,
+    /// The points for killing this enemy.
+    pub score: usize,
+    pub movement: Movement,
+}
+
+impl Default for EnemyKind {
+    fn default() -> Self {
+        EnemyKind {
+            name: String::new(),
+            art: "===>".to_string(),
+            speed: 0.05,
+            health: 3,
+            score: 100,
+            movement: Movement::Straight,
+        }
+    }
+}
+
+/// Makes later rounds of a wave script harder.
+/// Each time the script starts over, a new round begins.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Escalation {
+    /// Additional speed per round, relative to the enemy's speed.
+    pub speed: f32,
+    /// Additional health per round.
+    pub health: usize,
+    /// Additional enemies per group and round.
+    pub count: usize,
+    /// The delay of each wave is multiplied by this factor each round.
+    pub delay: f32,
+}
+
+impl Default for Escalation {
+    fn default() -> Self {
+        Escalation {
+            speed: 0.0,
+            health: 0,
+            count: 0,
+            delay: 1.0,
+        }
+    }
+}
+
+impl Escalation {
+    pub fn speed(&self, speed: f32, round: usize) -> f32 {
+        speed * (1.0 + self.speed * round as f32)
+    }
+
+    pub fn health(&self, health: usize, round: usize) -> usize {
+        health + self.health * round
+    }
+
+    pub fn count(&self, count: usize, round: usize) -> usize {
+        count + self.count * round
+    }
+
+    pub fn delay(&self, delay: Duration, round: usize) -> Duration {
+        delay.mul_f32(self.delay.max(0.0).powi(round as i32))
+    }
+}
+
+/// How a boss moves up and down, once it arrived on the right side of the screen.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum BossMovement {
+    /// Stay in the middle and bob up and down a little.
+    #[default]
+    Hover,
+    /// Sweep over the whole height of the screen.
+    Sweep,
+    /// Follow the copter.
+    Chase,
+}
+
+/// The projectiles, that are fired with each attack.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "pattern", rename_all = "kebab-case")]
+pub enum AttackPattern {
+    /// A single projectile aimed at the copter.
+    Aimed,
+    /// A fan of projectiles around the copter's direction.
+    Spread {
+        count: usize,
+        /// The angle of the whole fan in degrees.
+        #[serde(default = "default_spread_angle")]
+        angle: f32,
+    },
+    /// Projectiles in all directions.
+    Ring { count: usize },
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Attack {
+    #[serde(flatten)]
+    pub pattern: AttackPattern,
+    /// The time between two attacks in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub interval: Duration,
+    /// The projectile speed per second, relative to the window width.
+    #[serde(default = "default_projectile_speed")]
+    pub speed: f32,
+}
+
+/// A part of the boss with its own health.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct HitZone {
+    /// The column, row, width and height of the zone in characters of the boss' art.
+    pub area: [usize; 4],
+    pub health: usize,
+}
+
+/// The boss changes its behavior, once its health drops below a threshold.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Phase {
+    /// The phase starts, once the boss' health dropped to this fraction.
+    #[serde(default = "default_phase_health")]
+    pub health: f32,
+    #[serde(default)]
+    pub movement: BossMovement,
+    #[serde(default)]
+    pub attacks: Vec<Attack>,
+}
+
+/// A large enemy, which is only defeated once all of its hit zones are destroyed.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct BossKind {
+    #[serde(skip)]
+    pub name: String,
+    /// Multi-line ascii art.
+    pub art: String,
+    /// The ascii art of the boss' projectiles.
+    pub projectile: String,
+    /// The vertical speed per second, relative to the window height.
+    pub speed: f32,
+    /// The points for defeating the boss.
+    pub score: usize,
+    pub zones: Vec<HitZone>,
+    /// Sorted by their health threshold, starting with the first phase.
+    pub phases: Vec<Phase>,
+}
+
+impl Default for BossKind {
+    fn default() -> Self {
+        BossKind {
+            name: String::new(),
+            art: String::new(),
+            projectile: "*".to_string(),
+            speed: 0.2,
+            score: 5000,
+            zones: Vec::new(),
+            phases: Vec::new(),
+        }
+    }
+}
+
+impl BossKind {
+    pub fn max_health(&self) -> usize {
+        self.zones.iter().map(|zone| zone.health).sum()
+    }
+
+    /// The index of the phase for the given fraction of the boss' health.
+    pub fn phase(&self, health: f32) -> usize {
+        self.phases
+            .iter()
+            .rposition(|phase| health <= phase.health)
+            .unwrap_or(0)
+    }
+}
+
+/// A bunch of enemies of the same kind, which spawn together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    /// The index of the enemy's kind in [WaveScript::enemies].
+    pub enemy: usize,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wave {
+    /// The time since the previous wave.
+    pub delay: Duration,
+    pub groups: Vec<Group>,
+    /// The index of the boss in [WaveScript::bosses].
+    /// The script waits until it's defeated, before the next wave starts.
+    pub boss: Option<usize>,
+}
+
+/// The enemy kinds and the waves, in which they attack.
+/// The waves are repeated with escalating difficulty, once the last one has spawned.
+///
+/// Wave scripts are toml files like this one:
+///
+/// ```toml
+/// [escalation]
+/// speed = 0.1
+/// count = 2
+///
+/// [enemies.arrow]
+/// art = "===>"
+///
+/// [enemies.snake]
+/// art = "~~~>"
+/// speed = 0.08
+/// health = 1
+/// score = 150
+/// movement = { pattern = "zigzag", amplitude = 0.04, frequency = 1.5 }
+///
+/// [[waves]]
+/// delay = 5
+/// groups = [{ enemy = "arrow", count = 8 }, { enemy = "snake", count = 3 }]
+///
+/// [bosses.blimp]
+/// art = '''
+///  _______
+/// ( o   o )>
+///  \_____/
+/// '''
+/// zones = [{ area = [2, 1, 5, 1], health = 20 }]
+/// phases = [
+///     { attacks = [{ pattern = "aimed", interval = 1 }] },
+///     { health = 0.5, movement = "sweep", attacks = [{ pattern = "ring", count = 12, interval = 2 }] },
+/// ]
+///
+/// [[waves]]
+/// delay = 10
+/// boss = "blimp"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaveScript {
+    pub enemies: Vec<EnemyKind>,
+    pub bosses: Vec<BossKind>,
+    pub waves: Vec<Wave>,
+    pub escalation: Escalation,
+}
+
+#[derive(Deserialize)]
+struct WaveScriptFile {
+    #[serde(default)]
+    enemies: BTreeMap<String, EnemyKind>,
+    #[serde(default)]
+    bosses: BTreeMap<String, BossKind>,
+    #[serde(default)]
+    waves: Vec<WaveFile>,
+    #[serde(default)]
+    escalation: Escalation,
+}
+
+#[derive(Deserialize)]
+struct WaveFile {
+    #[serde(deserialize_with = "seconds")]
+    delay: Duration,
+    #[serde(default)]
+    groups: Vec<GroupFile>,
+    boss: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GroupFile {
+    enemy: String,
+    count: usize,
+}
+
+impl WaveScript {
+    /// Use the configured wave script.
+    /// If there's no such file, the enemy settings of the config are used for endless waves.
+    pub fn from_config(config: &CopterConfig) -> Result<WaveScript, WaveError> {
+        if !config.waves.exists() {
+            return Ok(WaveScript::classic(config));
+        }
+
+        let content = read_to_string(&config.waves).map_err(|source| WaveError::Io {
+            path: config.waves.clone(),
+            source,
+        })?;
+
+        WaveScript::parse(&config.waves, &content)
+    }
+
+    /// Identical waves of a single enemy kind, which are all taken from the config.
+    pub fn classic(config: &CopterConfig) -> WaveScript {
+        WaveScript {
+            enemies: vec![EnemyKind {
+                name: "arrow".to_string(),
+                speed: config.enemy_speed,
+                health: config.enemy_max_health,
+                ..Default::default()
+            }],
+            bosses: Vec::new(),
+            waves: vec![Wave {
+                delay: config.enemy_wave_timeout,
+                groups: vec![Group {
+                    enemy: 0,
+                    count: config.enemy_wave_size,
+                }],
+                boss: None,
+            }],
+            escalation: Escalation::default(),
+        }
+    }
+
+    pub fn parse(path: &Path, content: &str) -> Result<WaveScript, WaveError> {
+        let file: WaveScriptFile = toml::from_str(content).map_err(|source| WaveError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let names: Vec<String> = file.enemies.keys().cloned().collect();
+        let enemies = file
+            .enemies
+            .into_iter()
+            .map(|(name, kind)| EnemyKind { name, ..kind })
+            .collect();
+
+        let mut bosses = Vec::new();
+        for (name, mut boss) in file.bosses {
+            if boss.zones.is_empty() {
+                return Err(WaveError::NoHitZones {
+                    path: path.to_path_buf(),
+                    boss: name,
+                });
+            }
+            boss.name = name;
+            boss.phases.sort_by(|a, b| b.health.total_cmp(&a.health));
+            bosses.push(boss);
+        }
+
+        let mut waves = Vec::new();
+        for (index, wave) in file.waves.into_iter().enumerate() {
+            let mut groups = Vec::new();
+            for group in wave.groups {
+                let enemy = names
+                    .iter()
+                    .position(|name| *name == group.enemy)
+                    .ok_or_else(|| WaveError::UnknownEnemy {
+                        path: path.to_path_buf(),
+                        wave: index + 1,
+                        enemy: group.enemy.clone(),
+                    })?;
+                groups.push(Group {
+                    enemy,
+                    count: group.count,
+                });
+            }
+            let boss = match wave.boss {
+                Some(boss) => Some(
+                    bosses
+                        .iter()
+                        .position(|kind| kind.name == boss)
+                        .ok_or_else(|| WaveError::UnknownEnemy {
+                            path: path.to_path_buf(),
+                            wave: index + 1,
+                            enemy: boss.clone(),
+                        })?,
+                ),
+                None => None,
+            };
+            waves.push(Wave {
+                delay: wave.delay,
+                groups,
+                boss,
+            });
+        }
+
+        if waves.is_empty() {
+            return Err(WaveError::Empty {
+                path: path.to_path_buf(),
+            });
+        }
+
+        Ok(WaveScript {
+            enemies,
+            bosses,
+            waves,
+            escalation: file.escalation,
+        })
+    }
+}
+
+fn default_amplitude() -> f32 {
+    0.03
+}
+
+fn default_frequency() -> f32 {
+    1.0
+}
+
+fn default_radius() -> f32 {
+    0.2
+}
+
+fn default_angular_speed() -> f32 {
+    1.0
+}
+
+fn default_spacing() -> f32 {
+    0.03
+}
+
+fn default_spread_angle() -> f32 {
+    60.0
+}
+
+fn default_projectile_speed() -> f32 {
+    0.3
+}
+
+fn default_phase_health() -> f32 {
+    1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"
+        [escalation]
+        speed = 0.5
+        health = 1
+        count = 2
+        delay = 0.5
+
+        [enemies.arrow]
+        art = "===>"
+
+        [enemies.moon]
+        art = "(O)"
+        score = 300
+        movement = { pattern = "orbit", radius = 0.1 }
+
+        [[waves]]
+        delay = 5
+        groups = [{ enemy = "arrow", count = 8 }]
+
+        [[waves]]
+        delay = 2.5
+        groups = [{ enemy = "arrow", count = 4 }, { enemy = "moon", count = 1 }]
+    "#;
+
+    #[test]
+    fn scripts_are_parsed() {
+        let script = WaveScript::parse(Path::new("waves.toml"), SCRIPT).unwrap();
+
+        assert_eq!(script.enemies.len(), 2);
+        let moon = &script.enemies[script.waves[1].groups[1].enemy];
+        assert_eq!(moon.name, "moon");
+        assert_eq!(moon.score, 300);
+        assert_eq!(moon.health, 3);
+        assert_eq!(
+            moon.movement,
+            Movement::Orbit {
+                radius: 0.1,
+                angular_speed: 1.0
+            }
+        );
+        assert_eq!(script.waves[1].delay, Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn unknown_enemies_are_rejected() {
+        let content = "[[waves]]\ndelay = 1\ngroups = [{ enemy = \"ghost\", count = 1 }]\n";
+        let err = WaveScript::parse(Path::new("waves.toml"), content).unwrap_err();
+        assert!(matches!(err, WaveError::UnknownEnemy { wave: 1, .. }));
+
+        let err = WaveScript::parse(Path::new("waves.toml"), "[enemies.arrow]\n").unwrap_err();
+        assert!(matches!(err, WaveError::Empty { .. }));
+    }
+
+    #[test]
+    fn later_rounds_are_harder() {
+        let script = WaveScript::parse(Path::new("waves.toml"), SCRIPT).unwrap();
+        let escalation = &script.escalation;
+
+        assert_eq!(escalation.speed(0.1, 0), 0.1);
+        assert!((escalation.speed(0.1, 2) - 0.2).abs() < 0.001);
+        assert_eq!(escalation.health(3, 2), 5);
+        assert_eq!(escalation.count(8, 1), 10);
+        assert_eq!(
+            escalation.delay(Duration::from_secs(4), 2),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn formations_keep_their_shape() {
+        let movement = Movement::Formation { spacing: 0.01 };
+        let offsets: Vec<Vec2> = (0..3)
+            .map(|index| movement.formation_offset(index, 1000.0).unwrap())
+            .collect();
+        assert_eq!(offsets[0], Vec2::ZERO);
+        assert_eq!(offsets[1], Vec2::new(-10.0, -10.0));
+        assert_eq!(offsets[2], Vec2::new(-10.0, 10.0));
+        assert!(Movement::Straight.formation_offset(1, 1000.0).is_none());
+    }
+
+    #[test]
+    fn bosses_are_parsed() {
+        let content = r#"
+            [bosses.blimp]
+            art = "(o_o)"
+            zones = [{ area = [1, 0, 3, 1], health = 10 }]
+            phases = [
+                { health = 0.3, movement = "chase", attacks = [{ pattern = "ring", count = 8, interval = 1 }] },
+                { attacks = [{ pattern = "spread", count = 3, interval = 0.5, speed = 0.5 }] },
+            ]
+
+            [[waves]]
+            delay = 1
+            boss = "blimp"
+        "#;
+        let script = WaveScript::parse(Path::new("waves.toml"), content).unwrap();
+
+        let boss = &script.bosses[script.waves[0].boss.unwrap()];
+        assert_eq!(boss.name, "blimp");
+        assert_eq!(boss.max_health(), 10);
+        // Phases are sorted, so the phase for full health comes first.
+        assert_eq!(boss.phases[0].movement, BossMovement::Hover);
+        assert_eq!(
+            boss.phases[0].attacks[0].pattern,
+            AttackPattern::Spread {
+                count: 3,
+                angle: 60.0
+            }
+        );
+        assert_eq!(boss.phases[1].movement, BossMovement::Chase);
+        assert_eq!(boss.phase(1.0), 0);
+        assert_eq!(boss.phase(0.5), 0);
+        assert_eq!(boss.phase(0.3), 1);
+
+        let content = "[bosses.ghost]\nart = \"boo\"\n[[waves]]\ndelay = 1\n";
+        let err = WaveScript::parse(Path::new("waves.toml"), content).unwrap_err();
+        assert!(matches!(err, WaveError::NoHitZones { .. }));
+    }
+}
@@ -0,0 +1,437 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use macroquad::prelude::*;
+
+use super::draw::draw_hud_text;
+use super::shot::Shot;
+use super::{CopterSimulation, RoflcopterAnimation};
+use crate::helper::*;
+use crate::simulation::Step;
+use crate::state::State;
+
+/// Collecting the same power-up again upgrades the weapon up to this level.
+pub const MAX_LEVEL: usize = 3;
+/// The angle between two shots of the spread shot.
+const SPREAD_ANGLE: f32 = 12.0;
+/// How fast homing missiles turn towards their target in radians per second.
+const HOMING_TURN_RATE: f32 = 6.0;
+/// How long a laser beam can be seen.
+const BEAM_DURATION: Duration = Duration::from_millis(150);
+/// The blast radius of a bomb relative to the window width.
+const BOMB_RADIUS: f32 = 0.08;
+/// How long an explosion can be seen.
+const EXPLOSION_DURATION: Duration = Duration::from_millis(400);
+/// Power-ups start to blink, once they're about to vanish.
+const POWER_UP_BLINK: Duration = Duration::from_secs(3);
+
+/// All the different ways the copter can shoot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weapon {
+    /// The default weapon, a single straight shot.
+    Blaster,
+    /// A fan of straight shots.
+    Spread,
+    /// Slow missiles, that follow the closest enemy.
+    Homing,
+    /// A beam, that instantly hits everything in its way.
+    Laser,
+    /// Slow shots, that explode at their target and damage everything around them.
+    Bomb,
+}
+
+impl Weapon {
+    pub const ALL: [Weapon; 5] = [
+        Weapon::Blaster,
+        Weapon::Spread,
+        Weapon::Homing,
+        Weapon::Laser,
+        Weapon::Bomb,
+    ];
+    /// The weapons, that can be dropped by enemies.
+    pub const POWER_UPS: [Weapon; 4] =
+        [Weapon::Spread, Weapon::Homing, Weapon::Laser, Weapon::Bomb];
+
+    /// The index of the weapon's textures.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Weapon::Blaster => "BLASTER",
+            Weapon::Spread => "SPREAD",
+            Weapon::Homing => "HOMING",
+            Weapon::Laser => "LASER",
+            Weapon::Bomb => "BOMBS",
+        }
+    }
+
+    /// The ascii art of a single shot.
+    /// The laser's art is repeated along its beam.
+    pub fn art(self) -> &'static str {
+        match self {
+            Weapon::Blaster => "=>",
+            Weapon::Spread => "->",
+            Weapon::Homing => "LOL",
+            Weapon::Laser => "=",
+            Weapon::Bomb => "@",
+        }
+    }
+
+    /// The ascii art of the power-up, that grants this weapon.
+    pub fn power_up_art(self) -> &'static str {
+        match self {
+            Weapon::Blaster => "[=]",
+            Weapon::Spread => "[S]",
+            Weapon::Homing => "[H]",
+            Weapon::Laser => "[L]",
+            Weapon::Bomb => "[B]",
+        }
+    }
+
+    /// The speed of a shot per second, relative to the window width.
+    pub fn speed(self) -> f32 {
+        match self {
+            Weapon::Blaster | Weapon::Spread | Weapon::Laser => 1.0,
+            Weapon::Homing => 0.5,
+            Weapon::Bomb => 0.4,
+        }
+    }
+
+    /// The time between two shots. Heavy weapons take longer to reload.
+    pub fn cooldown(self, shot_timeout: Duration) -> Duration {
+        match self {
+            Weapon::Blaster | Weapon::Spread => shot_timeout,
+            Weapon::Homing => shot_timeout * 2,
+            Weapon::Laser => shot_timeout * 3,
+            Weapon::Bomb => shot_timeout * 4,
+        }
+    }
+
+    pub fn damage(self, level: usize) -> usize {
+        match self {
+            Weapon::Blaster | Weapon::Spread => 1,
+            Weapon::Homing => 2,
+            Weapon::Laser => level,
+            Weapon::Bomb => 1 + level,
+        }
+    }
+}
+
+/// A collectible power-up, that's dropped by killed enemies.
+#[derive(Debug, Clone)]
+pub struct PowerUp {
+    pub weapon: Weapon,
+    /// The middle of the power-up's art.
+    pub position: Vec2,
+    /// The time until the power-up vanishes.
+    pub timer: Duration,
+}
+
+/// A fired laser, which is only kept around to draw it.
+#[derive(Debug, Clone)]
+pub struct Beam {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub age: Duration,
+}
+
+/// An exploded bomb, which is only kept around to draw it.
+#[derive(Debug, Clone)]
+pub struct Explosion {
+    pub position: Vec2,
+    pub radius: f32,
+    pub age: Duration,
+}
+
+impl CopterSimulation {
+    /// Fire the current weapon from the copter towards the destination.
+    pub fn fire(&mut self, step: &Step, dest: Vec2) {
+        let position = self.muzzle(dest);
+        let angle = vec2_to_radian(dest - position);
+        let damage = self.weapon.damage(self.weapon_level);
+        let shot = Shot {
+            weapon: self.weapon,
+            position,
+            angle,
+            damage,
+            range: None,
+        };
+
+        match self.weapon {
+            Weapon::Blaster => self.shots.push(shot),
+            Weapon::Spread | Weapon::Homing => {
+                // The spread shot gets wider and there are more missiles with each level.
+                let count = match self.weapon {
+                    Weapon::Spread => 1 + 2 * self.weapon_level,
+                    _ => self.weapon_level,
+                };
+                let fan = SPREAD_ANGLE.to_radians() * (count - 1) as f32;
+                for index in 0..count {
+                    let offset = if count == 1 {
+                        0.0
+                    } else {
+                        -fan / 2.0 + fan * index as f32 / (count - 1) as f32
+                    };
+                    self.shots.push(Shot {
+                        angle: angle + offset,
+                        ..shot.clone()
+                    });
+                }
+            }
+            Weapon::Laser => self.fire_laser(step, position, angle, damage),
+            Weapon::Bomb => self.shots.push(Shot {
+                range: Some(position.distance(dest)),
+                ..shot
+            }),
+        }
+    }
+
+    /// The laser hits everything between the copter and the edge of the screen.
+    fn fire_laser(&mut self, step: &Step, start: Vec2, angle: f32, damage: usize) {
+        let length = Vec2::new(step.window_width, step.window_height).length();
+        let end = start + Vec2::new(angle.cos(), angle.sin()) * length;
+
+        let beam = Obb::new((start + end) / 2.0, Vec2::new(length, step.glyph.y), angle);
+        let (grid, enemy_boxes) = self.enemy_grid(step);
+        for index in grid.query(beam.bounds()) {
+            if enemy_boxes[index].intersects(&beam) {
+                let enemy = &mut self.enemies[index];
+                enemy.health = enemy.health.saturating_sub(damage);
+            }
+        }
+        self.hit_boss_along(step, start, end, damage);
+
+        self.beams.push(Beam {
+            start,
+            end,
+            age: Duration::from_secs(0),
+        });
+    }
+
+    /// Let a bomb explode and damage all enemies in its blast radius.
+    pub fn explode(&mut self, step: &Step, position: Vec2, damage: usize) {
+        let level = self.weapon_level.max(1) as f32;
+        let radius = BOMB_RADIUS * step.window_width * (1.0 + (level - 1.0) / 2.0);
+        for enemy in self.enemies.iter_mut() {
+            if enemy.position.distance(position) < radius {
+                enemy.health = enemy.health.saturating_sub(damage);
+            }
+        }
+
+        self.explosions.push(Explosion {
+            position,
+            radius,
+            age: Duration::from_secs(0),
+        });
+    }
+
+    /// Steer a homing missile towards the closest visible enemy or the boss.
+    pub fn steer_missile(&self, step: &Step, shot: &mut Shot) {
+        let target = self
+            .enemies
+            .iter()
+            .map(|enemy| enemy.position)
+            .filter(|position| outside_screen(step, *position).is_none())
+            .min_by(|a, b| {
+                a.distance(shot.position)
+                    .total_cmp(&b.distance(shot.position))
+            })
+            .or_else(|| self.boss_target(step));
+        let Some(target) = target else {
+            return;
+        };
+
+        // Turn by the shortest way, but not faster than the missile can.
+        let wanted = vec2_to_radian(target - shot.position);
+        let difference = (wanted - shot.angle + TAU / 2.0).rem_euclid(TAU) - TAU / 2.0;
+        let max_turn = HOMING_TURN_RATE * step.delta_secs();
+        shot.angle += difference.clamp(-max_turn, max_turn);
+    }
+
+    /// Tick the weapon's timer and fall back to the blaster, once it runs out.
+    /// Beams and explosions fade away.
+    pub fn update_weapon(&mut self, step: &Step) {
+        if self.weapon != Weapon::Blaster {
+            self.weapon_timer = self.weapon_timer.saturating_sub(step.delta);
+            if self.weapon_timer.is_zero() {
+                self.reset_weapon();
+            }
+        }
+
+        for beam in self.beams.iter_mut() {
+            beam.age += step.delta;
+        }
+        self.beams.retain(|beam| beam.age < BEAM_DURATION);
+        for explosion in self.explosions.iter_mut() {
+            explosion.age += step.delta;
+        }
+        self.explosions
+            .retain(|explosion| explosion.age < EXPLOSION_DURATION);
+    }
+
+    pub fn reset_weapon(&mut self) {
+        self.weapon = Weapon::Blaster;
+        self.weapon_level = 1;
+        self.weapon_timer = Duration::from_secs(0);
+    }
+
+    /// Killed enemies sometimes leave a random power-up behind.
+    pub fn drop_power_up(&mut self, step: &Step, position: Vec2) {
+        if step.rng.gen_range(0.0, 1.0) >= self.power_up_chance {
+            return;
+        }
+
+        let weapon = Weapon::POWER_UPS[step.rng.gen_range(0, Weapon::POWER_UPS.len())];
+        self.power_ups.push(PowerUp {
+            weapon,
+            position,
+            timer: self.power_up_lifetime,
+        });
+    }
+
+    /// Collect power-ups, that are touched by the copter, and remove the ones that vanished.
+    pub fn update_power_ups(&mut self, step: &Step) {
+        let hitbox = self.copter_hitbox();
+        let mut collected = Vec::new();
+        self.power_ups.retain_mut(|power_up| {
+            power_up.timer = power_up.timer.saturating_sub(step.delta);
+            if hitbox.contains(power_up.position) {
+                collected.push(power_up.weapon);
+                return false;
+            }
+
+            !power_up.timer.is_zero()
+        });
+
+        for weapon in collected {
+            self.collect(weapon);
+        }
+    }
+
+    /// Switch to a new weapon or upgrade the current one.
+    /// Either way, the weapon lasts for the full duration again.
+    pub fn collect(&mut self, weapon: Weapon) {
+        if self.weapon == weapon {
+            self.weapon_level = (self.weapon_level + 1).min(MAX_LEVEL);
+        } else {
+            self.weapon = weapon;
+            self.weapon_level = 1;
+        }
+        self.weapon_timer = self.weapon_duration;
+    }
+
+    /// The autopilot flies towards power-ups, whenever there's one on the screen.
+    pub fn power_up_dest(&self, step: &Step) -> Option<Vec2> {
+        let copter_middle = self.copter_middle();
+        let power_up = self
+            .power_ups
+            .iter()
+            .filter(|power_up| outside_screen(step, power_up.position).is_none())
+            .min_by(|a, b| {
+                a.position
+                    .distance(copter_middle)
+                    .total_cmp(&b.position.distance(copter_middle))
+            })?;
+
+        let max =
+            (Vec2::new(step.window_width, step.window_height) - self.copter_size).max(Vec2::ZERO);
+        Some((power_up.position - self.copter_size / 2.0).clamp(Vec2::ZERO, max))
+    }
+}
+
+fn texture_size(texture: &Texture2D) -> Vec2 {
+    Vec2::new(texture.width(), texture.height())
+}
+
+impl RoflcopterAnimation {
+    /// Draw all power-ups. They blink, once they're about to vanish.
+    pub fn draw_power_ups(&self, state: &State) {
+        let blink = (state.clock.elapsed().as_millis() / 200).is_multiple_of(2);
+        for power_up in self.simulation.power_ups.iter() {
+            if power_up.timer < POWER_UP_BLINK && blink {
+                continue;
+            }
+
+            let texture = self.textures.power_ups[power_up.weapon.index()];
+            let position = power_up.position - texture_size(&texture) / 2.0;
+            draw_texture_ex(
+                texture,
+                position.x,
+                position.y,
+                WHITE,
+                DrawTextureParams {
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    /// Draw laser beams by repeating the laser's art along them.
+    pub fn draw_beams(&self) {
+        let texture = self.textures.shots[Weapon::Laser.index()];
+        for beam in self.simulation.beams.iter() {
+            let alpha = 1.0 - beam.age.as_secs_f32() / BEAM_DURATION.as_secs_f32();
+            let direction = (beam.end - beam.start).normalize_or_zero();
+            let pieces = (beam.start.distance(beam.end) / texture.width().max(1.0)) as usize;
+            for piece in 0..pieces {
+                let middle = beam.start + direction * texture.width() * (piece as f32 + 0.5);
+                let position = middle - texture_size(&texture) / 2.0;
+                draw_texture_ex(
+                    texture,
+                    position.x,
+                    position.y,
+                    Color::new(1.0, 1.0, 1.0, alpha),
+                    DrawTextureParams {
+                        rotation: vec2_to_radian(direction),
+                        flip_y: true,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+
+    /// Explosions are a growing ring of debris.
+    pub fn draw_explosions(&self) {
+        let texture = self.textures.explosion;
+        for explosion in self.simulation.explosions.iter() {
+            let progress = explosion.age.as_secs_f32() / EXPLOSION_DURATION.as_secs_f32();
+            let color = Color::new(1.0, 1.0 - progress, 0.0, 1.0 - progress);
+            for index in 0..12 {
+                let angle = TAU * index as f32 / 12.0;
+                let offset = Vec2::new(angle.cos(), angle.sin()) * explosion.radius * progress;
+                let position = explosion.position + offset - texture_size(&texture) / 2.0;
+                draw_texture_ex(
+                    texture,
+                    position.x,
+                    position.y,
+                    color,
+                    DrawTextureParams {
+                        flip_y: true,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+
+    /// Show the current weapon and its remaining time below the score.
+    pub fn draw_weapon(&self, state: &State) {
+        let simulation = &self.simulation;
+        if simulation.weapon == Weapon::Blaster {
+            return;
+        }
+
+        let text = format!(
+            "{} LV{}  {}s",
+            simulation.weapon.name(),
+            simulation.weapon_level,
+            simulation.weapon_timer.as_secs() + 1
+        );
+        let margin = state.font_dimensions.width;
+        let position = Vec2::new(margin, margin + state.font_dimensions.height * 1.5);
+        draw_hud_text(state, &text, position, state.palette.colors[0]);
+    }
+}
@@ -0,0 +1,36 @@
+use roflcopter_snake_lib::snake_game_collection::SnakeGameCollection;
+
+use super::Animation;
+use crate::state::State;
+
+/// The minimal interface of animations from other crates.
+///
+/// Those crates don't know about our [State], so they only need to be able to update and draw
+/// themselves. Wrap them in an [Adapter] to register them as a full [Animation].
+pub trait ExternalAnimation {
+    fn update(&mut self);
+    fn draw(&self);
+}
+
+/// Turns any [ExternalAnimation] into an [Animation].
+pub struct Adapter<T: ExternalAnimation>(pub T);
+
+impl<T: ExternalAnimation> Animation for Adapter<T> {
+    fn update(&mut self, _state: &State) {
+        self.0.update();
+    }
+
+    fn draw(&self, _state: &State) {
+        self.0.draw();
+    }
+}
+
+impl ExternalAnimation for SnakeGameCollection {
+    fn update(&mut self) {
+        SnakeGameCollection::update(self);
+    }
+
+    fn draw(&self) {
+        SnakeGameCollection::draw(self);
+    }
+}
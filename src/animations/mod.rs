@@ -1,39 +1,65 @@
-use macroquad::prelude::Vec2;
-use roflcopter_snake_lib::snake_game_collection::SnakeGameCollection;
-
 pub use crate::helper::*;
 pub use copter::*;
+pub use external::{Adapter, ExternalAnimation};
+pub use registry::AnimationRegistry;
 pub use wall::WallAnimation;
+pub use word_chaos::WordChaosAnimation;
 
-use crate::state::State;
+use macroquad::input::KeyCode;
 
-use self::word_chaos::WordChaosAnimation;
+use crate::{input::Input, state::State};
 
 pub mod copter;
+pub mod external;
+pub mod registry;
 pub mod wall;
 pub mod word_chaos;
 
-pub enum Animation {
-    Wall(WallAnimation),
-    Copter(RoflcopterAnimation),
-    WordChaos(WordChaosAnimation),
-    Snake(SnakeGameCollection),
-}
+/// The interface that's shared by all animations.
+///
+/// Only one animation runs at a time. The [State] takes care of switching between them
+/// and calls the respective hooks.
+pub trait Animation {
+    /// Advance the animation by a single frame.
+    fn update(&mut self, state: &State);
+
+    /// Draw the current frame of the animation.
+    fn draw(&self, state: &State);
+
+    /// Called, after the window size changed.
+    /// Anything that depends on the screen resolution or font size should be updated here.
+    fn on_resize(&mut self, _state: &State) {}
 
-impl Animation {
-    pub fn new_copter(state: &State) -> Animation {
-        let position = Vec2::new(state.window_width / 2.0, state.window_height / 2.0);
-        Animation::Copter(RoflcopterAnimation::new(state, position))
+    /// Called, after the palette has been reloaded.
+    /// By default, it's handled like a resize, as textures may contain the old colors.
+    fn on_palette_change(&mut self, state: &State) {
+        self.on_resize(state);
     }
 
-    pub fn new_wall() -> Animation {
-        Animation::Wall(WallAnimation::new())
+    /// Called once per frame with the latest input snapshot.
+    fn on_input(&mut self, _state: &State, _input: &Input) {}
+
+    /// Called, right after the animation became the active animation.
+    fn on_enter(&mut self, _state: &State) {}
+
+    /// Called, right before the animation is replaced by the next one.
+    /// Textures of the animation should be deleted here, as they aren't freed on drop.
+    fn on_exit(&mut self, _state: &State) {}
+
+    /// Whether the animation is done before its time is up.
+    /// The [State] switches to the next animation right away.
+    fn is_finished(&self) -> bool {
+        false
     }
-    pub fn new_word_chaos(state: &State) -> Animation {
-        Animation::WordChaos(WordChaosAnimation::new(state))
+
+    /// Keys, that are currently used by the animation.
+    /// The global hotkeys of these keys are disabled, while the animation uses them.
+    fn reserved_keys(&self) -> &[KeyCode] {
+        &[]
     }
 
-    pub fn new_snake() -> Animation {
-        Animation::Snake(SnakeGameCollection::new())
+    /// Additional lines for the debug overlay.
+    fn debug_info(&self) -> Vec<String> {
+        Vec::new()
     }
 }
@@ -0,0 +1,88 @@
+use macroquad::prelude::Vec2;
+use roflcopter_snake_lib::snake_game_collection::SnakeGameCollection;
+
+use super::*;
+use crate::state::State;
+
+/// A function that creates a new instance of an animation.
+pub type Constructor = Box<dyn Fn(&State) -> Box<dyn Animation>>;
+
+/// All animations that are known to the application, keyed by their name.
+///
+/// The registration order is kept, which is the order in which animations are listed
+/// to the user.
+pub struct AnimationRegistry {
+    animations: Vec<(String, Constructor)>,
+}
+
+impl AnimationRegistry {
+    /// Create an empty registry.
+    pub fn new() -> AnimationRegistry {
+        AnimationRegistry { animations: vec![] }
+    }
+
+    /// Create a registry with all animations that ship with this crate.
+    pub fn with_defaults() -> AnimationRegistry {
+        let mut registry = AnimationRegistry::new();
+
+        registry.register("wall", |state| Box::new(WallAnimation::new(state)));
+        registry.register("copter", |state| {
+            let position = Vec2::new(state.window_width / 2.0, state.window_height / 2.0);
+            Box::new(RoflcopterAnimation::new(state, position))
+        });
+        registry.register("word-chaos", |state| {
+            Box::new(WordChaosAnimation::new(state))
+        });
+        registry.register_external("snake", SnakeGameCollection::new);
+
+        registry
+    }
+
+    /// Register an animation under the given name.
+    /// An existing animation with the same name will be replaced.
+    pub fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn(&State) -> Box<dyn Animation> + 'static,
+    {
+        let constructor: Constructor = Box::new(constructor);
+        match self.animations.iter_mut().find(|(known, _)| known == name) {
+            Some((_, existing)) => *existing = constructor,
+            None => self.animations.push((name.to_string(), constructor)),
+        }
+    }
+
+    /// Register an animation of another crate through an [Adapter].
+    pub fn register_external<T, F>(&mut self, name: &str, constructor: F)
+    where
+        T: ExternalAnimation + 'static,
+        F: Fn() -> T + 'static,
+    {
+        self.register(name, move |_| Box::new(Adapter(constructor())));
+    }
+
+    /// Create a new instance of the animation with the given name.
+    pub fn create(&self, name: &str, state: &State) -> Option<Box<dyn Animation>> {
+        self.animations
+            .iter()
+            .find(|(known, _)| known == name)
+            .map(|(_, constructor)| constructor(state))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.animations.iter().any(|(known, _)| known == name)
+    }
+
+    /// The names of all registered animations in registration order.
+    pub fn names(&self) -> Vec<&str> {
+        self.animations
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+impl Default for AnimationRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
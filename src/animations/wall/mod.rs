@@ -1,22 +1,53 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use macroquad::prelude::*;
 
+use super::Animation;
+use crate::helper::{cycle_colors, graphemes};
+use crate::simulation::Step;
 use crate::state::State;
 
 mod shader;
 
+/// The wall prefers words with these tags, as long words don't make for a nice pattern.
+const WORD_TAGS: [&str; 1] = ["short"];
+
 #[derive(Debug, Clone)]
 pub struct WallAnimation {
     y_offset: f32,
     x_offset: f32,
+    /// The graphemes of the word on the wall.
+    word: Vec<String>,
+    /// The color of each grapheme.
+    colors: Vec<Color>,
 }
 
 impl WallAnimation {
-    pub fn new() -> WallAnimation {
+    pub fn new(state: &State) -> WallAnimation {
+        // Fall back to the configured word, if the wordlist doesn't contain any short words.
+        let (word, colors) = match state.random_word_tagged(&WORD_TAGS) {
+            Some(entry) => {
+                // Phrases are shown on a single line.
+                let text = entry.text.lines().collect::<Vec<_>>().join(" ");
+                let word: Vec<String> = graphemes(&text).into_iter().map(String::from).collect();
+                let colors = match entry.palette {
+                    Some(palette) => cycle_colors(&palette, word.len()),
+                    None => state.palette.colors_for(word.len()),
+                };
+                (word, colors)
+            }
+            None => (
+                graphemes(&state.word)
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                state.colors.clone(),
+            ),
+        };
+
         WallAnimation {
             y_offset: 0.0,
             x_offset: 0.0,
+            word,
+            colors,
         }
     }
 
@@ -24,19 +55,18 @@ impl WallAnimation {
     ///
     /// This function is responsible for the actual animation, by determining and updating the offset
     /// to the original start position.
-    pub fn update(&mut self, state: &State) {
-        // The current system time, determines the current x/y movement rate.
-        let start = SystemTime::now();
-        let time = start.duration_since(UNIX_EPOCH).unwrap().as_millis() as f64;
+    pub fn step(&mut self, step: &Step) {
+        // The elapsed time, determines the current x/y movement rate.
+        let time = step.elapsed.as_millis() as f64;
 
         // The delta time in combination with the movement rate is used to determine the moved
         // amount.
-        let dt = get_frame_time();
+        let dt = step.delta_secs();
 
         // Calculate the amount that has been moved since the last frame.
         // We oscilate through a sinus curve every few seconds.
         // The sinus is offseted by 1, which helps us to stay in positive range.
-        let y_movement_speed = state.font_dimensions.height * 20.0;
+        let y_movement_speed = step.glyph.y * 20.0;
         let mut y_rate = ((time / 1000.0f64).sin() + 1.0) as f32;
         // Enforce a minimum movement rate of 0.2
         y_rate += y_rate.signum() * 0.2;
@@ -45,68 +75,34 @@ impl WallAnimation {
         self.y_offset += moved_amount;
 
         // Set the movement speed relative to the text glyph width.
-        let x_movement_speed = state.font_dimensions.width * 1.0;
+        let x_movement_speed = step.glyph.x * 1.0;
         let x_rate = ((time * 0.3f64 / 1000.0f64).sin()) as f32;
         let moved_amount = dt * x_rate * x_movement_speed;
         self.x_offset += moved_amount;
 
         // Prevent floating too far away, due to floating point imprecision
-        if self.x_offset < -state.window_width / 2.0 {
-            self.x_offset = -state.window_width / 2.0;
-        } else if self.x_offset > state.window_width / 2.0 {
-            self.x_offset = state.window_width / 2.0;
-        }
-    }
-
-    /// Draw the roflcopter wall everything to the canvas.
-    pub fn draw(&self, state: &State) {
-        let mut line = (self.y_offset / state.font_dimensions.height) as usize;
-
-        // We start at -100, as lines can shift upwards slowly
-        let mut used_height = -100.0;
-        while (state.window_height + 100.0) > used_height {
-            self.draw_line(state, used_height, line);
-            line += 1;
-            used_height += state.font_dimensions.height;
+        if self.x_offset < -step.window_width / 2.0 {
+            self.x_offset = -step.window_width / 2.0;
+        } else if self.x_offset > step.window_width / 2.0 {
+            self.x_offset = step.window_width / 2.0;
         }
-
-        let ultra_rofl_size = (state.window_width / 5.0) as u16;
-        let word_size = measure_text("ROFL", Some(state.font), ultra_rofl_size, 1.0);
-        let mut ultra_rofl_position =
-            Vec2::new(state.window_width / 2.0, state.window_height / 2.0);
-        ultra_rofl_position.x -= word_size.width / 2.0;
-
-        draw_text_ex(
-            "ROFL",
-            ultra_rofl_position.x,
-            ultra_rofl_position.y,
-            TextParams {
-                font: state.font,
-                font_size: ultra_rofl_size,
-                font_scale: 1.0,
-                color: BLACK,
-                ..Default::default()
-            },
-        );
-
-        shader::draw_shader(state);
     }
 
     /// Draw one single line.
     /// Each line is drawn depending on the current animation offset, state and text dimensions.
     fn draw_line(&self, state: &State, current_height: f32, line_count: usize) {
         let glyph_width = state.font_dimensions.width;
-        let word_length = state.word.len();
+        let word_length = self.word.len();
 
         // Calculate the word and color offset for the current line.
         let offset = line_count % word_length;
-        let (word, colors) = offsetted_word_and_colors(state, offset);
+        let (word, colors) = self.offsetted_word_and_colors(offset);
 
         // Runner width variable for this line.
         let mut current_width: f32 = -state.window_width;
         let mut color_offset = 0;
         loop {
-            for character in word.chars() {
+            for grapheme in word.iter() {
                 // Exit condition, stop the loop, if the next char doesn't fit onto the screen
                 if current_width + glyph_width > state.window_width * 2.0 {
                     return;
@@ -114,7 +110,7 @@ impl WallAnimation {
 
                 // We have a smooth movement, which is why we move in
                 let height = current_height - self.y_offset % state.font_dimensions.height;
-                let width = current_width - self.x_offset * state.word.len() as f32 * 2.0;
+                let width = current_width - self.x_offset * word_length as f32 * 2.0;
 
                 // Don't draw the char, if it cannot be seen anyway.
                 let mut skip = false;
@@ -129,7 +125,7 @@ impl WallAnimation {
                 if !skip {
                     // Draw the character at the next position.
                     draw_text_ex(
-                        &character.to_string(),
+                        grapheme,
                         width,
                         height,
                         TextParams {
@@ -142,32 +138,66 @@ impl WallAnimation {
                     );
                 }
                 current_width += glyph_width;
-                color_offset = (color_offset + 1) % state.word.len();
+                color_offset = (color_offset + 1) % word_length;
             }
             // Add a space between this and the next word
             current_width += glyph_width;
-            color_offset = (color_offset + 1) % state.word.len();
+            color_offset = (color_offset + 1) % word_length;
         }
     }
 }
 
-impl Default for WallAnimation {
-    fn default() -> Self {
-        Self::new()
+impl Animation for WallAnimation {
+    fn update(&mut self, state: &State) {
+        self.step(&Step::from_state(state));
+    }
+
+    /// Draw the roflcopter wall everything to the canvas.
+    fn draw(&self, state: &State) {
+        let mut line = (self.y_offset / state.font_dimensions.height) as usize;
+
+        // We start at -100, as lines can shift upwards slowly
+        let mut used_height = -100.0;
+        while (state.window_height + 100.0) > used_height {
+            self.draw_line(state, used_height, line);
+            line += 1;
+            used_height += state.font_dimensions.height;
+        }
+
+        let ultra_rofl_size = (state.window_width / 5.0) as u16;
+        let word_size = measure_text("ROFL", Some(state.font), ultra_rofl_size, 1.0);
+        let mut ultra_rofl_position =
+            Vec2::new(state.window_width / 2.0, state.window_height / 2.0);
+        ultra_rofl_position.x -= word_size.width / 2.0;
+
+        draw_text_ex(
+            "ROFL",
+            ultra_rofl_position.x,
+            ultra_rofl_position.y,
+            TextParams {
+                font: state.font,
+                font_size: ultra_rofl_size,
+                font_scale: 1.0,
+                color: BLACK,
+                ..Default::default()
+            },
+        );
+
+        shader::draw_shader(state);
     }
 }
 
-/// Each line is offsetted to the previous by one.
-/// This calculates the color and character offset for the current line and prepares
-/// it for further usage.
-fn offsetted_word_and_colors(state: &State, offset: usize) -> (String, Vec<Color>) {
-    let mut word_start = state.word.to_owned();
-    let mut word_end = word_start.split_off(offset);
-    word_end.push_str(&word_start);
+impl WallAnimation {
+    /// Each line is offsetted to the previous by one.
+    /// This calculates the color and character offset for the current line and prepares
+    /// it for further usage.
+    fn offsetted_word_and_colors(&self, offset: usize) -> (Vec<&str>, Vec<Color>) {
+        let mut word: Vec<&str> = self.word.iter().map(String::as_str).collect();
+        word.rotate_left(offset);
 
-    let mut colors_start = state.colors.clone();
-    let mut colors_end = colors_start.split_off(offset);
-    colors_end.append(&mut colors_start);
+        let mut colors = self.colors.clone();
+        colors.rotate_left(offset);
 
-    (word_end, colors_end)
+        (word, colors)
+    }
 }
@@ -0,0 +1,1013 @@
+use std::{f32::consts::PI, time::Duration};
+
+use macroquad::prelude::*;
+
+use super::tools::{Autopilot, Tool, AUTOPILOT_KEY};
+use crate::{config::WordChaosConfig, helper::*, simulation::Step};
+
+#[derive(Debug, Clone)]
+pub struct Word {
+    /// The graphemes `[start, end)` of the current word, that are left of this word.
+    pub start: usize,
+    pub end: usize,
+    pub position: Vec2,
+    pub acceleration: Vec2,
+    pub color: Color,
+    pub angle: f32,
+    pub angle_rotation: f32,
+    pub font_size: u16,
+}
+
+impl Word {
+    /// The amount of graphemes that are left of this word.
+    pub fn length(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// The size of a single grapheme of this word.
+    /// The font size is the one, that the word's texture is rendered with.
+    pub fn glyph(&self, step: &Step, font_size: u16) -> Vec2 {
+        step.glyph * FONT_SCALE * self.font_size as f32 / font_size.max(1) as f32
+    }
+
+    /// The box around the word, just as it's drawn.
+    pub fn obb(&self, step: &Step, font_size: u16) -> Obb {
+        // The texture has an additional line for descenders.
+        let size = Vec2::new(self.length() as f32, 2.0) * self.glyph(step, font_size);
+        Obb::from_texture(self.position, size, self.angle + PI)
+    }
+
+    /// The middle of the graphemes `[start, end)` of this word, which turns with the word.
+    pub fn slice_center(&self, step: &Step, font_size: u16, start: usize, end: usize) -> Vec2 {
+        let obb = self.obb(step, font_size);
+        let [axis, _] = obb.axes();
+        let offset = (start + end) as f32 / 2.0 - self.start as f32 - self.length() as f32 / 2.0;
+        obb.center + axis * offset * self.glyph(step, font_size).x
+    }
+
+    /// The middle of a single grapheme, which turns with the word.
+    pub fn letter_position(&self, step: &Step, font_size: u16, column: usize) -> Vec2 {
+        self.slice_center(step, font_size, column, column + 1)
+    }
+
+    /// The graphemes `[start, end)` of this word as a word of their own, right where they are.
+    pub fn piece(&self, step: &Step, font_size: u16, start: usize, end: usize) -> Word {
+        let center = self.slice_center(step, font_size, start, end);
+        let mut piece = Word {
+            start,
+            end,
+            ..self.clone()
+        };
+        piece.set_center(step, font_size, center);
+        piece
+    }
+
+    /// Move the word, so the middle of its box ends up at the given position.
+    pub fn set_center(&mut self, step: &Step, font_size: u16, center: Vec2) {
+        self.position += center - self.obb(step, font_size).center;
+    }
+
+    /// Push the word back onto the screen, if its box sticks out on any side.
+    fn keep_on_screen(&mut self, step: &Step, font_size: u16) {
+        let bounds = self.obb(step, font_size).bounds();
+        self.position.x -= (bounds.right() - (step.window_width - 1.0)).max(0.0);
+        self.position.x += (1.0 - bounds.left()).max(0.0);
+        self.position.y -= (bounds.bottom() - (step.window_height - 1.0)).max(0.0);
+        self.position.y += (1.0 - bounds.top()).max(0.0);
+    }
+}
+
+/// Words are rendered larger than the main font.
+pub const FONT_SCALE: f32 = 1.5;
+
+/// Broken off letters are slowly pulled down, while they fade away.
+const LETTER_GRAVITY: f32 = 1.0;
+const LETTER_LIFETIME: Duration = Duration::from_millis(1500);
+const MAX_LETTERS: usize = 1000;
+
+/// Impacts, that are faster than this part of the maximum speed, split words in the middle.
+const HARD_IMPACT: f32 = 0.9;
+/// Pieces of a word are pushed apart with this part of the maximum speed.
+const SPLIT_SPEED: f32 = 0.2;
+
+/// The game logic of the word chaos animation.
+///
+/// Each word is a piece of the current word, which flies around.
+/// Whenever it hits the edge of the screen, the letter at the end closest to the edge breaks off
+/// and flies on its own. Hard impacts split words in the middle instead.
+/// Single letters fall apart into a particle, once they hit the edge.
+/// With physics enabled, words also bounce off each other and neighbouring pieces merge back together.
+/// The mouse pushes words around with the current [Tool] and flings them with a right-drag.
+#[derive(Debug, Clone)]
+pub struct WordChaosSimulation {
+    pub words: Vec<Word>,
+    /// The letters, that fell apart at the edge.
+    pub letters: ParticleSystem,
+    /// Whether letters get the color of their word.
+    /// Words with their own palette aren't tinted.
+    pub tinted: bool,
+    word_limit: usize,
+    /// The amount of graphemes of the current word.
+    word_length: usize,
+    font_size: u16,
+    /// All font sizes, letters that break off can have.
+    font_sizes: Vec<u16>,
+    /// The colors, that words can have.
+    palette: Vec<Color>,
+
+    spawn_timeout: Duration,
+    spawn_timer: Duration,
+
+    /// Whether words collide with each other.
+    physics: bool,
+    merge_chance: f32,
+
+    pub tool: Tool,
+    /// The radius of the tools relative to the window height.
+    tool_radius: f32,
+    /// The acceleration of the tools in window widths per second squared.
+    tool_strength: f32,
+    /// The virtual cursor, that's used instead of the mouse.
+    pub autopilot: Option<Autopilot>,
+    /// Where the tool has been used during the last step.
+    pub cursor: Option<Vec2>,
+    /// The mouse position of the last step of a right-drag.
+    drag: Option<Vec2>,
+}
+
+impl WordChaosSimulation {
+    pub fn new(
+        config: &WordChaosConfig,
+        font_size: u16,
+        palette: Vec<Color>,
+    ) -> WordChaosSimulation {
+        WordChaosSimulation {
+            words: vec![],
+            letters: ParticleSystem::new(LETTER_GRAVITY, MAX_LETTERS),
+            tinted: true,
+            word_limit: config.word_limit,
+            word_length: 0,
+            font_size,
+            font_sizes: vec![font_size],
+            palette,
+            spawn_timeout: config.spawn_timeout,
+            spawn_timer: Duration::from_millis(0),
+            physics: config.physics,
+            merge_chance: config.merge_chance,
+            tool: config.tool,
+            tool_radius: config.tool_radius,
+            tool_strength: config.tool_strength,
+            autopilot: config.autopilot.then(|| Autopilot::new(Vec2::ZERO)),
+            cursor: None,
+            drag: None,
+        }
+    }
+
+    /// Restart the simulation with a single new word at a random position.
+    /// The font sizes are those, that are available for drawing.
+    pub fn restart(&mut self, step: &Step, word_length: usize, mut font_sizes: Vec<u16>) {
+        // Sort the sizes, so the picked sizes only depend on the random number stream.
+        font_sizes.sort_unstable();
+        self.font_sizes = font_sizes;
+        self.word_length = word_length;
+        self.words.clear();
+        self.letters.clear();
+        self.spawn_word(step, random_position_on_screen(step));
+    }
+
+    /// New words get their colors from the given palette.
+    pub fn set_palette(&mut self, palette: Vec<Color>) {
+        self.palette = palette;
+    }
+
+    /// Spawn the current word at the given position.
+    pub fn spawn_word(&mut self, step: &Step, position: Vec2) {
+        let rng = step.rng;
+        self.words.push(Word {
+            position,
+            acceleration: random_vector_with_lenght(rng, rng.gen_range(200.0, 400.0)),
+            start: 0,
+            end: self.word_length,
+            color: random_color(rng, &self.palette),
+            angle: rng.gen_range(0.0, 2.0 * PI),
+            angle_rotation: rng.gen_range(0.1, 0.2),
+            font_size: self.font_size,
+        });
+    }
+
+    /// All words and their letters are gone. It's time for a new word.
+    pub fn is_finished(&self) -> bool {
+        self.words.is_empty() && self.letters.particles.is_empty()
+    }
+
+    /// Advance the simulation by a single step.
+    pub fn step(&mut self, step: &Step) {
+        let dt = step.delta_secs();
+        let max_speed = step.window_width / 4.0;
+        let rng = step.rng;
+
+        self.handle_input(step);
+        let drag = self.drag(step);
+        let (tool, cursor, radius) = (self.tool, self.cursor, self.tool_radius(step));
+        let strength = self.tool_strength * step.window_width;
+
+        let mut new_words = vec![];
+        let mut words_to_remove = vec![];
+        let mut letters = vec![];
+        let (word_length, font_size, tinted) = (self.word_length, self.font_size, self.tinted);
+        let current_words = self.words.len();
+
+        for (index, word) in self.words.iter_mut().enumerate() {
+            let center = word.obb(step, font_size).center;
+            if let Some((mouse, velocity)) = drag {
+                if center.distance(mouse) < radius {
+                    word.acceleration = velocity.clamp_length_max(max_speed);
+                }
+            }
+            if let Some(cursor) = cursor {
+                if tool == Tool::Freeze && center.distance(cursor) < radius {
+                    continue;
+                }
+                let force = tool.force(cursor, center, radius, strength);
+                word.acceleration = (word.acceleration + force * dt).clamp_length_max(max_speed);
+            }
+
+            word.angle += word.angle_rotation * dt;
+            word.position += word.acceleration * dt;
+            let bounds = word.obb(step, font_size).bounds();
+            let Some(collision) = edge_collision(step, bounds, word.acceleration) else {
+                continue;
+            };
+
+            // A single letter has nothing left to lose, so it falls apart.
+            if word.length() == 1 {
+                letters.push(fall_apart(step, word, word_length, font_size, tinted));
+                words_to_remove.push(index);
+                continue;
+            }
+
+            let impact = match collision {
+                Direction::Left | Direction::Right => word.acceleration.x.abs(),
+                Direction::Top | Direction::Bottom => word.acceleration.y.abs(),
+            };
+            let room = current_words + new_words.len() < self.word_limit;
+            let piece = if impact >= HARD_IMPACT * max_speed && room {
+                let (first, second) = split(step, word, font_size, max_speed);
+                *word = first;
+                Some(second)
+            } else {
+                let mut letter = break_off(step, word, &collision, font_size, max_speed);
+                if room {
+                    letter.font_size = self.font_sizes[rng.gen_range(0, self.font_sizes.len() - 1)];
+                    letter.color = random_color(rng, &self.palette);
+                    Some(letter)
+                } else {
+                    // There's no room for another word, so the letter falls apart right away.
+                    letters.push(fall_apart(step, &letter, word_length, font_size, tinted));
+                    None
+                }
+            };
+
+            bounce(step, word, &collision, font_size, max_speed);
+            if let Some(mut piece) = piece {
+                bounce(step, &mut piece, &collision, font_size, max_speed);
+                new_words.push(piece);
+            }
+        }
+        words_to_remove.reverse();
+        for index in words_to_remove.iter() {
+            self.words.remove(*index);
+        }
+
+        // Append all new words.
+        self.words.append(&mut new_words);
+        if self.physics {
+            self.collide_words(step, max_speed);
+        }
+
+        for letter in letters {
+            self.letters.spawn(letter);
+        }
+        self.letters.update(step);
+    }
+
+    /// Let words bounce off each other, as if their length was their mass.
+    /// Sometimes neighbouring pieces merge back into a longer piece of the current word instead.
+    /// Hard impacts split both words in the middle.
+    fn collide_words(&mut self, step: &Step, max_speed: f32) {
+        let boxes: Vec<Obb> = self
+            .words
+            .iter()
+            .map(|word| word.obb(step, self.font_size))
+            .collect();
+        let mut grid = SpatialGrid::new(step.glyph.x * FONT_SCALE * self.word_length as f32);
+        for (index, obb) in boxes.iter().enumerate() {
+            grid.insert(index, obb.bounds());
+        }
+
+        let mut merged = vec![false; self.words.len()];
+        let mut shattered = vec![];
+        for (first, second) in grid.pairs() {
+            if merged[first] || merged[second] || !boxes[first].intersects(&boxes[second]) {
+                continue;
+            }
+
+            // Words, that are already flying apart, are left alone.
+            let normal = (boxes[second].center - boxes[first].center).normalize_or_zero();
+            let (a, b) = (&self.words[first], &self.words[second]);
+            let approach = (b.acceleration - a.acceleration).dot(normal);
+            if approach >= 0.0 {
+                continue;
+            }
+
+            let (mass_a, mass_b) = (a.length() as f32, b.length() as f32);
+            let neighbours = a.end == b.start || b.end == a.start;
+            if neighbours && step.rng.gen_range(0.0, 1.0) < self.merge_chance {
+                // The momentum is kept and the heavier word keeps its looks.
+                let momentum = a.acceleration * mass_a + b.acceleration * mass_b;
+                let center = (boxes[first].center * mass_a + boxes[second].center * mass_b)
+                    / (mass_a + mass_b);
+                let b = b.clone();
+                let word = &mut self.words[first];
+                if b.length() > word.length() {
+                    word.angle = b.angle;
+                    word.color = b.color;
+                    word.font_size = b.font_size;
+                }
+                word.start = word.start.min(b.start);
+                word.end = word.end.max(b.end);
+                word.acceleration = momentum / (mass_a + mass_b);
+                word.set_center(step, self.font_size, center);
+                merged[second] = true;
+                continue;
+            }
+
+            // An elastic collision along the line between both words.
+            let impulse = -2.0 * approach / (1.0 / mass_a + 1.0 / mass_b);
+            let a = &mut self.words[first];
+            a.acceleration =
+                (a.acceleration - normal * impulse / mass_a).clamp_length_max(max_speed);
+            let b = &mut self.words[second];
+            b.acceleration =
+                (b.acceleration + normal * impulse / mass_b).clamp_length_max(max_speed);
+
+            if -approach >= HARD_IMPACT * max_speed {
+                shattered.extend([first, second]);
+            }
+        }
+
+        shattered.sort_unstable();
+        shattered.dedup();
+        let mut pieces = vec![];
+        let remaining = merged.iter().filter(|merged| !**merged).count();
+        for index in shattered {
+            let word = &self.words[index];
+            if merged[index] || word.length() < 2 || remaining + pieces.len() >= self.word_limit {
+                continue;
+            }
+            let (first, second) = split(step, word, self.font_size, max_speed);
+            self.words[index] = first;
+            pieces.push(second);
+        }
+
+        let mut index = 0;
+        self.words.retain(|_| {
+            index += 1;
+            !merged[index - 1]
+        });
+        self.words.append(&mut pieces);
+    }
+
+    /// The radius around the cursor, in which words are affected by the tools.
+    pub fn tool_radius(&self, step: &Step) -> f32 {
+        self.tool_radius * step.window_height
+    }
+
+    /// Switch between the autopilot and the mouse.
+    /// The autopilot starts at the mouse position.
+    pub fn toggle_autopilot(&mut self, step: &Step) {
+        self.autopilot = match self.autopilot {
+            Some(_) => None,
+            None => Some(Autopilot::new(step.input.mouse_position)),
+        };
+    }
+
+    /// Pick the tool and the cursor, that uses it.
+    /// The mouse only uses the tool, while its left button is held down.
+    fn handle_input(&mut self, step: &Step) {
+        for tool in Tool::ALL {
+            if step.input.is_key_pressed(tool.key()) {
+                self.tool = tool;
+            }
+        }
+        if step.input.is_key_pressed(AUTOPILOT_KEY) {
+            self.toggle_autopilot(step);
+        }
+        // The autopilot never lets go, so it would spawn words forever and the word would never change.
+        if self.autopilot.is_some() && self.tool == Tool::Spawn {
+            self.tool = Tool::FORCES[step.rng.gen_range(0, Tool::FORCES.len())];
+        }
+
+        self.cursor = match &mut self.autopilot {
+            Some(autopilot) => Some(autopilot.update(step)),
+            None => step
+                .input
+                .mouse_left_down
+                .then_some(step.input.mouse_position),
+        };
+
+        self.spawn_timer += step.delta;
+        if self.spawn_timer > self.spawn_timeout {
+            if let (Tool::Spawn, Some(cursor)) = (self.tool, self.cursor) {
+                self.spawn_word(step, cursor)
+            }
+            self.spawn_timer = Duration::from_secs(0);
+        }
+    }
+
+    /// The mouse position and speed during a right-drag.
+    /// Words around the mouse are carried along and keep its speed, once they're let go.
+    fn drag(&mut self, step: &Step) -> Option<(Vec2, Vec2)> {
+        if !step.input.mouse_right_down {
+            self.drag = None;
+            return None;
+        }
+
+        let mouse = step.input.mouse_position;
+        let previous = self.drag.replace(mouse).unwrap_or(mouse);
+        Some((mouse, (mouse - previous) / step.delta_secs().max(0.001)))
+    }
+}
+
+/// The edge of the screen, that a word crossed while flying towards it.
+/// Words, that are already on their way back, don't collide again.
+fn edge_collision(step: &Step, bounds: Rect, velocity: Vec2) -> Option<Direction> {
+    if bounds.right() >= step.window_width && velocity.x > 0.0 {
+        Some(Direction::Right)
+    } else if bounds.left() <= 0.0 && velocity.x < 0.0 {
+        Some(Direction::Left)
+    } else if bounds.bottom() >= step.window_height && velocity.y > 0.0 {
+        Some(Direction::Bottom)
+    } else if bounds.top() <= 0.0 && velocity.y < 0.0 {
+        Some(Direction::Top)
+    } else {
+        None
+    }
+}
+
+/// How far a point is away from the given edge of the screen.
+fn edge_distance(step: &Step, position: Vec2, edge: &Direction) -> f32 {
+    match edge {
+        Direction::Right => step.window_width - position.x,
+        Direction::Left => position.x,
+        Direction::Bottom => step.window_height - position.y,
+        Direction::Top => position.y,
+    }
+}
+
+/// Push the word back onto the screen and let it bounce off the edge.
+/// Every bounce makes it a bit faster and spin faster.
+fn bounce(step: &Step, word: &mut Word, edge: &Direction, font_size: u16, max_speed: f32) {
+    let rng = step.rng;
+    word.angle_rotation *= rng.gen_range(1.1, 1.4);
+
+    // Slightly change acceleration
+    word.acceleration = rotate_vec2(word.acceleration, rng.gen_range(0.1, 0.4));
+    word.acceleration *= rng.gen_range(1.1, 1.2);
+
+    word.keep_on_screen(step, font_size);
+    match edge {
+        Direction::Right => word.acceleration.x = -word.acceleration.x.abs(),
+        Direction::Left => word.acceleration.x = word.acceleration.x.abs(),
+        Direction::Bottom => word.acceleration.y = -word.acceleration.y.abs(),
+        Direction::Top => word.acceleration.y = word.acceleration.y.abs(),
+    }
+
+    word.acceleration = word.acceleration.clamp_length_max(max_speed);
+}
+
+/// Push two pieces of a word apart, along the line between their middles.
+fn fly_apart(step: &Step, font_size: u16, first: &mut Word, second: &mut Word, speed: f32) {
+    let direction = (second.obb(step, font_size).center - first.obb(step, font_size).center)
+        .normalize_or_zero();
+    first.acceleration -= direction * speed;
+    second.acceleration += direction * speed;
+}
+
+/// The letter at the end of the word, that's closest to the edge, breaks off.
+/// It keeps the speed and spin of the word, the word keeps the rest of its graphemes.
+fn break_off(
+    step: &Step,
+    word: &mut Word,
+    edge: &Direction,
+    font_size: u16,
+    max_speed: f32,
+) -> Word {
+    let first = word.letter_position(step, font_size, word.start);
+    let last = word.letter_position(step, font_size, word.end - 1);
+    let column = if edge_distance(step, first, edge) <= edge_distance(step, last, edge) {
+        word.start
+    } else {
+        word.end - 1
+    };
+
+    let mut letter = word.piece(step, font_size, column, column + 1);
+    let mut rest = if column == word.start {
+        word.piece(step, font_size, column + 1, word.end)
+    } else {
+        word.piece(step, font_size, word.start, column)
+    };
+    fly_apart(
+        step,
+        font_size,
+        &mut rest,
+        &mut letter,
+        SPLIT_SPEED * max_speed,
+    );
+    *word = rest;
+
+    letter
+}
+
+/// Break a word in the middle into two pieces, that fly apart.
+fn split(step: &Step, word: &Word, font_size: u16, max_speed: f32) -> (Word, Word) {
+    let middle = word.start + word.length() / 2;
+    let mut first = word.piece(step, font_size, word.start, middle);
+    let mut second = word.piece(step, font_size, middle, word.end);
+    fly_apart(
+        step,
+        font_size,
+        &mut first,
+        &mut second,
+        SPLIT_SPEED * max_speed,
+    );
+
+    (first, second)
+}
+
+/// A single letter falls apart into a particle, that tumbles away.
+/// The word length is the amount of graphemes in the word's texture.
+fn fall_apart(
+    step: &Step,
+    word: &Word,
+    word_length: usize,
+    font_size: u16,
+    tinted: bool,
+) -> Particle {
+    let rng = step.rng;
+    let column = word.start;
+    // The word's texture contains each grapheme of the current word once.
+    let columns = word_length.max(1) as f32;
+    let shape = ParticleShape::Glyph {
+        texture: 0,
+        source: Some(Rect::new(column as f32 / columns, 0.0, 1.0 / columns, 1.0)),
+    };
+    let velocity =
+        word.acceleration * 0.5 + random_vector_with_lenght(rng, rng.gen_range(50.0, 150.0));
+    let color = if tinted { word.color } else { WHITE };
+
+    Particle {
+        angle: word.angle + PI,
+        spin: rng.gen_range(-4.0, 4.0),
+        size: word.font_size as f32 / font_size as f32,
+        color,
+        fade_to: Color { a: 0.0, ..color },
+        ..Particle::new(
+            shape,
+            word.letter_position(step, font_size, column),
+            velocity,
+            LETTER_LIFETIME,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::rand::RandGenerator;
+
+    use super::*;
+    use crate::input::Input;
+
+    const WIDTH: f32 = 1280.0;
+    const HEIGHT: f32 = 720.0;
+
+    fn step<'a>(input: &'a Input, rng: &'a RandGenerator) -> Step<'a> {
+        Step {
+            input,
+            delta: Duration::from_millis(10),
+            elapsed: Duration::from_secs(1),
+            window_width: WIDTH,
+            window_height: HEIGHT,
+            glyph: Vec2::new(10.0, 20.0),
+            rng,
+        }
+    }
+
+    fn simulation(word_limit: usize) -> WordChaosSimulation {
+        let config = WordChaosConfig {
+            word_limit,
+            ..Default::default()
+        };
+        let mut simulation = WordChaosSimulation::new(&config, 30, vec![RED, GREEN]);
+        simulation.word_length = 5;
+        simulation.font_sizes = vec![28, 29, 30, 31, 32];
+        simulation
+    }
+
+    /// A word that's moving to the right with its middle just in front of the right edge.
+    fn word_at_right_edge(length: usize) -> Word {
+        Word {
+            start: 0,
+            end: length,
+            position: Vec2::new(WIDTH - 1.0 - length as f32 * 5.0, 300.0),
+            acceleration: Vec2::new(200.0, 0.0),
+            color: WHITE,
+            angle: 0.0,
+            angle_rotation: 0.1,
+            font_size: 30,
+        }
+    }
+
+    #[test]
+    fn words_move_without_collision() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        let mut word = word_at_right_edge(5);
+        word.position = Vec2::new(500.0, 300.0);
+        simulation.words.push(word);
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.words.len(), 1);
+        assert_eq!(simulation.words[0].length(), 5);
+        assert_eq!(simulation.words[0].position, Vec2::new(502.0, 300.0));
+    }
+
+    #[test]
+    fn word_splits_and_bounces_at_the_edge() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.words.push(word_at_right_edge(5));
+
+        let step = step(&input, &rng);
+        simulation.step(&step);
+
+        assert_eq!(simulation.words.len(), 2);
+        let (word, split) = (&simulation.words[0], &simulation.words[1]);
+        // The word lost a character and now flies back to the left.
+        assert_eq!((word.start, word.end), (1, 5));
+        assert!(word.acceleration.x < 0.0);
+        assert!(word.obb(&step, 30).bounds().right() < WIDTH);
+        // The letter, that broke off, bounced off as well.
+        assert_eq!((split.start, split.end), (0, 1));
+        assert!(split.acceleration.x < 0.0);
+        assert!(split.obb(&step, 30).bounds().right() < WIDTH);
+        assert!(simulation.font_sizes.contains(&split.font_size));
+        assert!(word.acceleration.length() <= WIDTH / 4.0);
+    }
+
+    #[test]
+    fn rotated_words_bounce_with_their_shape() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        // An upright word, whose middle is far away from the top edge, but its end isn't.
+        let mut word = word_at_right_edge(10);
+        word.position = Vec2::new(565.0, 30.0);
+        word.angle = -PI / 2.0;
+        word.angle_rotation = 0.0;
+        word.acceleration = Vec2::new(0.0, -200.0);
+        simulation.words.push(word);
+
+        let step = step(&input, &rng);
+        simulation.step(&step);
+
+        let word = &simulation.words[0];
+        assert_eq!(word.length(), 9);
+        assert!(word.acceleration.y > 0.0);
+        assert!(word.obb(&step, 30).bounds().top() >= 0.0);
+    }
+
+    #[test]
+    fn single_characters_vanish_at_the_edge() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.words.push(word_at_right_edge(1));
+
+        simulation.step(&step(&input, &rng));
+
+        // The last letter tumbles away, before the next word starts.
+        assert!(simulation.words.is_empty());
+        assert_eq!(simulation.letters.particles.len(), 1);
+        assert!(!simulation.is_finished());
+
+        for _ in 0..150 {
+            simulation.step(&step(&input, &rng));
+        }
+        assert!(simulation.is_finished());
+    }
+
+    #[test]
+    fn the_end_closest_to_the_edge_breaks_off() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        // Turned around, the last letter is the one at the right edge.
+        let mut word = word_at_right_edge(5);
+        word.angle = PI;
+        simulation.words.push(word);
+
+        simulation.step(&step(&input, &rng));
+
+        let (word, letter) = (&simulation.words[0], &simulation.words[1]);
+        assert_eq!((word.start, word.end), (0, 4));
+        assert_eq!((letter.start, letter.end), (4, 5));
+        // The letter keeps the spin of its word.
+        assert!(letter.angle_rotation > 0.1);
+    }
+
+    #[test]
+    fn hard_impacts_split_words_in_the_middle() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        let mut word = word_at_right_edge(4);
+        word.acceleration = Vec2::new(300.0, 0.0);
+        simulation.words.push(word);
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.words.len(), 2);
+        let (first, second) = (&simulation.words[0], &simulation.words[1]);
+        assert_eq!((first.start, first.end), (0, 2));
+        assert_eq!((second.start, second.end), (2, 4));
+        assert!(first.acceleration.x < 0.0 && second.acceleration.x < 0.0);
+    }
+
+    #[test]
+    fn word_limit_prevents_splitting() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(1);
+        simulation.words.push(word_at_right_edge(5));
+
+        simulation.step(&step(&input, &rng));
+
+        // The word still loses a character, but it falls apart right away.
+        assert_eq!(simulation.words.len(), 1);
+        assert_eq!(simulation.words[0].length(), 4);
+        let letter = &simulation.letters.particles[0];
+        assert_eq!(
+            letter.shape,
+            ParticleShape::Glyph {
+                texture: 0,
+                source: Some(Rect::new(0.0, 0.0, 0.2, 1.0)),
+            }
+        );
+        assert_eq!(letter.color, WHITE);
+        assert!(letter.position.x > WIDTH - 50.0);
+    }
+
+    #[test]
+    fn mouse_spawns_words_after_timeout() {
+        let rng = RandGenerator::new();
+        let input = Input {
+            mouse_position: Vec2::new(200.0, 200.0),
+            mouse_moved: false,
+            mouse_left_down: true,
+            ..Default::default()
+        };
+        let mut simulation = simulation(10);
+        simulation.spawn_timer = simulation.spawn_timeout;
+
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.words.len(), 1);
+        assert_eq!(simulation.words[0].length(), 5);
+
+        // The timer has been reset, so no word is spawned in the next step.
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.words.len(), 1);
+    }
+
+    #[test]
+    fn restart_spawns_a_single_word() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.words.push(word_at_right_edge(3));
+
+        let step = step(&input, &rng);
+        simulation.restart(&step, 7, vec![12, 10, 11]);
+
+        assert_eq!(simulation.words.len(), 1);
+        assert_eq!(simulation.words[0].length(), 7);
+        assert_eq!(simulation.font_sizes, vec![10, 11, 12]);
+        assert!(outside_screen(&step, simulation.words[0].position).is_none());
+    }
+
+    /// Two pieces `[start, end)` of the current word, that are about to hit each other head-on.
+    fn head_on(
+        simulation: &mut WordChaosSimulation,
+        first: (usize, usize),
+        second: (usize, usize),
+    ) {
+        simulation.physics = true;
+        for ((start, end), x, speed) in [(first, 400.0, 100.0), (second, 412.0, -100.0)] {
+            let mut word = word_at_right_edge(end - start);
+            word.start = start;
+            word.end = end;
+            word.position = Vec2::new(x, 300.0);
+            word.acceleration = Vec2::new(speed, 0.0);
+            word.angle_rotation = 0.0;
+            simulation.words.push(word);
+        }
+    }
+
+    #[test]
+    fn words_bounce_off_each_other() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.merge_chance = 0.0;
+        head_on(&mut simulation, (0, 2), (0, 2));
+
+        simulation.step(&step(&input, &rng));
+
+        // Words of the same length swap their speed.
+        assert_eq!(simulation.words.len(), 2);
+        assert!((simulation.words[0].acceleration.x + 100.0).abs() < 0.001);
+        assert!((simulation.words[1].acceleration.x - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn words_merge_into_longer_words() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.merge_chance = 1.0;
+        head_on(&mut simulation, (0, 1), (1, 4));
+
+        simulation.step(&step(&input, &rng));
+
+        // The momentum of both words is kept.
+        assert_eq!(simulation.words.len(), 1);
+        assert_eq!((simulation.words[0].start, simulation.words[0].end), (0, 4));
+        assert!((simulation.words[0].acceleration.x + 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn only_neighbouring_pieces_merge() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.merge_chance = 1.0;
+        head_on(&mut simulation, (0, 2), (3, 5));
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.words.len(), 2);
+        assert!(simulation.words.iter().all(|word| word.length() == 2));
+    }
+
+    #[test]
+    fn words_shatter_on_hard_impacts() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.merge_chance = 0.0;
+        head_on(&mut simulation, (0, 2), (3, 5));
+        simulation.words[0].acceleration.x = 150.0;
+        simulation.words[1].acceleration.x = -150.0;
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.words.len(), 4);
+        assert!(simulation.words.iter().all(|word| word.length() == 1));
+    }
+
+    #[test]
+    fn words_pass_through_each_other_without_physics() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        head_on(&mut simulation, (0, 2), (0, 2));
+        simulation.physics = false;
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.words[0].acceleration.x, 100.0);
+    }
+
+    /// A resting word with its middle at (537.5, 330) and the mouse somewhere around it.
+    fn word_and_mouse(
+        simulation: &mut WordChaosSimulation,
+        tool: Tool,
+        mouse: Vec2,
+        left: bool,
+        right: bool,
+    ) -> Input {
+        let mut word = word_at_right_edge(5);
+        word.position = Vec2::new(500.0, 300.0);
+        word.angle_rotation = 0.0;
+        simulation.words.push(word);
+        simulation.tool = tool;
+
+        Input {
+            mouse_position: mouse,
+            mouse_left_down: left,
+            mouse_right_down: right,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn number_keys_switch_tools() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation(10);
+        let input = Input {
+            keys_pressed: vec![KeyCode::Key3, AUTOPILOT_KEY],
+            ..Default::default()
+        };
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.tool, Tool::Repel);
+        // The autopilot uses the tool without a mouse.
+        assert!(simulation.autopilot.is_some());
+        assert!(simulation.cursor.is_some());
+    }
+
+    #[test]
+    fn attracted_words_turn_towards_the_cursor() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation(10);
+        let mouse = Vec2::new(480.0, 330.0);
+        let input = word_and_mouse(&mut simulation, Tool::Attract, mouse, true, false);
+
+        simulation.step(&step(&input, &rng));
+        assert!(simulation.words[0].acceleration.x < 200.0);
+
+        // Without the left mouse button, nothing happens.
+        let input = word_and_mouse(&mut simulation, Tool::Attract, mouse, false, false);
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.words[1].acceleration.x, 200.0);
+    }
+
+    #[test]
+    fn frozen_words_stand_still() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation(10);
+        let mouse = Vec2::new(537.5, 330.0);
+        let input = word_and_mouse(&mut simulation, Tool::Freeze, mouse, true, false);
+
+        simulation.step(&step(&input, &rng));
+
+        assert_eq!(simulation.words[0].position, Vec2::new(500.0, 300.0));
+    }
+
+    #[test]
+    fn right_drags_fling_words() {
+        let rng = RandGenerator::new();
+        let mut simulation = simulation(10);
+        let mouse = Vec2::new(537.5, 330.0);
+        let input = word_and_mouse(&mut simulation, Tool::Spawn, mouse, false, true);
+
+        // A resting mouse holds the word in place.
+        simulation.step(&step(&input, &rng));
+        assert_eq!(simulation.words[0].acceleration, Vec2::ZERO);
+
+        let input = Input {
+            mouse_position: mouse + Vec2::new(1.0, 0.0),
+            mouse_right_down: true,
+            ..Default::default()
+        };
+        simulation.step(&step(&input, &rng));
+        assert!((simulation.words[0].acceleration.x - 100.0).abs() < 0.01);
+
+        // The word keeps the speed of the drag, once it's let go.
+        simulation.step(&step(&Input::default(), &rng));
+        assert!((simulation.words[0].acceleration.x - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn autopilot_lets_the_word_fall_apart() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        let step = step(&input, &rng);
+        simulation.restart(&step, 5, vec![30]);
+        simulation.autopilot = Some(Autopilot::new(Vec2::new(640.0, 360.0)));
+
+        simulation.step(&step);
+        assert_ne!(simulation.tool, Tool::Spawn);
+
+        // Ten minutes are plenty of time for all letters to hit an edge.
+        for _ in 0..60_000 {
+            if simulation.is_finished() {
+                break;
+            }
+            simulation.step(&step);
+        }
+        assert!(simulation.is_finished());
+    }
+
+    #[test]
+    fn new_words_use_the_reloaded_palette() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let mut simulation = simulation(10);
+        simulation.set_palette(vec![BLUE]);
+
+        simulation.restart(&step(&input, &rng), 5, vec![30]);
+
+        assert_eq!(simulation.words[0].color, BLUE);
+    }
+}
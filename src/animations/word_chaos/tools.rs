@@ -0,0 +1,182 @@
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+use crate::{helper::*, simulation::Step};
+
+/// Switches between the autopilot and the mouse.
+pub const AUTOPILOT_KEY: KeyCode = KeyCode::M;
+
+/// All keys, that switch the tool or the autopilot.
+pub const TOOL_KEYS: [KeyCode; 6] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    AUTOPILOT_KEY,
+];
+
+/// The speed of the autopilot's cursor in window widths per second.
+const CURSOR_SPEED: f32 = 0.3;
+
+/// What happens around the cursor, while the left mouse button is held down.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Tool {
+    /// Spawn the current word at the cursor.
+    #[default]
+    Spawn,
+    /// Pull words towards the cursor, like a gravity well.
+    Attract,
+    /// Push words away from the cursor.
+    Repel,
+    /// Let words swirl around the cursor.
+    Vortex,
+    /// Time stands still for all words around the cursor.
+    Freeze,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 5] = [
+        Tool::Spawn,
+        Tool::Attract,
+        Tool::Repel,
+        Tool::Vortex,
+        Tool::Freeze,
+    ];
+
+    /// The tools, that push words around instead of spawning new ones.
+    pub const FORCES: [Tool; 4] = [Tool::Attract, Tool::Repel, Tool::Vortex, Tool::Freeze];
+
+    /// The number key, that selects this tool.
+    pub fn key(self) -> KeyCode {
+        TOOL_KEYS[Tool::ALL.iter().position(|tool| *tool == self).unwrap_or(0)]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Spawn => "Spawn",
+            Tool::Attract => "Attract",
+            Tool::Repel => "Repel",
+            Tool::Vortex => "Vortex",
+            Tool::Freeze => "Freeze",
+        }
+    }
+
+    /// The acceleration of a word at the given position.
+    /// It's strongest at the cursor and fades out towards the edge of the radius.
+    pub fn force(self, cursor: Vec2, position: Vec2, radius: f32, strength: f32) -> Vec2 {
+        let offset = cursor - position;
+        let distance = offset.length();
+        if distance >= radius {
+            return Vec2::ZERO;
+        }
+
+        let direction = offset.normalize_or_zero() * strength * (1.0 - distance / radius);
+        match self {
+            Tool::Attract => direction,
+            Tool::Repel => -direction,
+            Tool::Vortex => direction.perp(),
+            Tool::Spawn | Tool::Freeze => Vec2::ZERO,
+        }
+    }
+}
+
+/// A virtual cursor, that follows random curves over the screen.
+/// It lets the tools work without a mouse.
+#[derive(Debug, Clone)]
+pub struct Autopilot {
+    pub position: Vec2,
+    /// The start, control point and end of a quadratic bezier curve.
+    curve: [Vec2; 3],
+    progress: f32,
+    /// The time it takes to follow the whole curve in seconds.
+    duration: f32,
+}
+
+impl Autopilot {
+    /// The cursor picks its first curve right away.
+    pub fn new(position: Vec2) -> Autopilot {
+        Autopilot {
+            position,
+            curve: [position; 3],
+            progress: 1.0,
+            duration: 1.0,
+        }
+    }
+
+    /// Move the cursor along its curve and pick a new one, once the end is reached.
+    pub fn update(&mut self, step: &Step) -> Vec2 {
+        if self.progress >= 1.0 {
+            let control = random_position_on_screen(step);
+            let end = random_position_on_screen(step);
+            self.curve = [self.position, control, end];
+            // The way via the control point is a bit longer than the curve, which is good enough.
+            let length = self.position.distance(control) + control.distance(end);
+            self.duration = (length / (step.window_width * CURSOR_SPEED)).max(0.1);
+            self.progress = 0.0;
+        }
+
+        self.progress = (self.progress + step.delta_secs() / self.duration).min(1.0);
+        let [start, control, end] = self.curve;
+        let t = self.progress;
+        self.position = start.lerp(control, t).lerp(control.lerp(end, t), t);
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use macroquad::rand::RandGenerator;
+
+    use super::*;
+    use crate::input::Input;
+
+    #[test]
+    fn forces_fade_out_towards_the_edge() {
+        let cursor = Vec2::new(100.0, 100.0);
+        let near = Tool::Attract.force(cursor, Vec2::new(90.0, 100.0), 50.0, 10.0);
+        let far = Tool::Attract.force(cursor, Vec2::new(60.0, 100.0), 50.0, 10.0);
+
+        assert!(near.x > far.x && far.x > 0.0);
+        assert_eq!(
+            Tool::Repel.force(cursor, Vec2::new(90.0, 100.0), 50.0, 10.0),
+            -near
+        );
+        // The vortex pushes sideways.
+        assert_eq!(
+            Tool::Vortex
+                .force(cursor, Vec2::new(90.0, 100.0), 50.0, 10.0)
+                .x,
+            0.0
+        );
+        assert_eq!(
+            Tool::Attract.force(cursor, Vec2::new(0.0, 100.0), 50.0, 10.0),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn autopilot_stays_on_the_screen() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let step = Step {
+            input: &input,
+            delta: Duration::from_millis(50),
+            elapsed: Duration::from_secs(1),
+            window_width: 1280.0,
+            window_height: 720.0,
+            glyph: Vec2::new(10.0, 20.0),
+            rng: &rng,
+        };
+        let mut autopilot = Autopilot::new(Vec2::new(640.0, 360.0));
+
+        let first = autopilot.update(&step);
+        assert_ne!(first, Vec2::new(640.0, 360.0));
+        for _ in 0..500 {
+            let position = autopilot.update(&step);
+            assert!(outside_screen(&step, position).is_none());
+        }
+    }
+}
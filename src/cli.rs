@@ -0,0 +1,103 @@
+use std::{path::PathBuf, str::FromStr};
+
+use clap::{ArgAction, Parser};
+
+use crate::fonts::FontMode;
+
+#[derive(Parser, Debug, Clone)]
+#[command(
+    name = "roflcopter",
+    about = "ROFL ROFL ROFL ROFL - Some totally useless ascii animations.",
+    author,
+    version
+)]
+pub struct CliArguments {
+    /// Verbose mode (-v, -vv)
+    #[arg(short, long, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Path to a config file.
+    /// Defaults to `roflcopter/config.toml` in the XDG config directory.
+    #[arg(short, long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// The animation that should be shown first, e.g. `wall`, `copter`, `word-chaos` or `snake`.
+    /// Defaults to the first entry of the playlist.
+    #[arg(short, long)]
+    pub animation: Option<String>,
+
+    /// The duration of a single animation in seconds.
+    #[arg(short, long)]
+    pub duration: Option<u64>,
+
+    /// Run in a window of the given size instead of fullscreen, e.g. `1280x720`.
+    #[arg(short, long, value_name = "WIDTHxHEIGHT")]
+    pub windowed: Option<WindowSize>,
+
+    /// The seed for the random number generator.
+    /// If none is given, the current time will be used. The seed is printed on startup,
+    /// so a run can be reproduced by passing it again.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Path to a wordlist. Either plain text with one word per line or a `.toml`/`.json` file.
+    #[arg(long, value_name = "PATH")]
+    pub wordlist: Option<PathBuf>,
+
+    /// Path to a ttf font or the name of a bundled font, e.g. `RobotoMono-Bold`.
+    #[arg(long, value_name = "PATH|NAME")]
+    pub font: Option<PathBuf>,
+
+    /// Use the configured font for all animations (`fixed`),
+    /// or go through the bundled fonts (`cycle`) or pick a random one (`random`) per animation.
+    #[arg(long, value_enum)]
+    pub font_mode: Option<FontMode>,
+
+    /// The color palette. Either a built-in theme (`rainbow`, `pastel`, `neon`, `monochrome`,
+    /// `high-contrast`) or the path to a GIMP palette (`.gpl`) or a list of hex colors.
+    #[arg(long, value_name = "THEME|PATH")]
+    pub palette: Option<String>,
+
+    /// Render the animations with a fixed timestep and save each frame as PNG into this directory.
+    /// User input is ignored, so the same seed and settings always produce the same frames.
+    #[arg(long, value_name = "DIR")]
+    pub export_frames: Option<PathBuf>,
+
+    /// The frames per second of the export.
+    #[arg(
+        long,
+        default_value_t = 60,
+        requires = "export_frames",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub fps: u32,
+
+    /// The amount of frames that should be exported.
+    #[arg(long, default_value_t = 600, requires = "export_frames")]
+    pub frames: u64,
+}
+
+/// The window size in pixels, as passed via `--windowed WIDTHxHEIGHT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSize {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl FromStr for WindowSize {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Expected a size like '1280x720', got '{input}'");
+
+        let (width, height) = input.split_once(['x', 'X']).ok_or_else(error)?;
+        let width: i32 = width.trim().parse().map_err(|_| error())?;
+        let height: i32 = height.trim().parse().map_err(|_| error())?;
+
+        if width <= 0 || height <= 0 {
+            return Err(format!("Window size must be positive, got '{input}'"));
+        }
+
+        Ok(WindowSize { width, height })
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use macroquad::prelude::get_frame_time;
+
+/// The source of time for the state and all animations.
+///
+/// Animations must never ask macroquad or the system for the current time.
+/// Otherwise, runs with a fixed timestep wouldn't be reproducible.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    /// If set, every frame advances the clock by exactly this duration.
+    timestep: Option<Duration>,
+    delta: Duration,
+    elapsed: Duration,
+}
+
+impl Clock {
+    /// A clock that follows the actual time between two rendered frames.
+    pub fn realtime() -> Clock {
+        Clock {
+            timestep: None,
+            delta: Duration::from_secs(0),
+            elapsed: Duration::from_secs(0),
+        }
+    }
+
+    /// A clock that advances by a fixed duration every frame.
+    pub fn fixed(timestep: Duration) -> Clock {
+        Clock {
+            timestep: Some(timestep),
+            delta: Duration::from_secs(0),
+            elapsed: Duration::from_secs(0),
+        }
+    }
+
+    /// Advance the clock by a single frame.
+    pub fn tick(&mut self) {
+        self.delta = match self.timestep {
+            Some(timestep) => timestep,
+            None => Duration::from_secs_f32(get_frame_time()),
+        };
+        self.elapsed += self.delta;
+    }
+
+    /// The time between the last and the current frame.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// The time between the last and the current frame in seconds.
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// The total time since the clock has been started.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clocks_advance_by_their_timestep() {
+        let mut clock = Clock::fixed(Duration::from_millis(20));
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+
+        for _ in 0..50 {
+            clock.tick();
+        }
+        assert_eq!(clock.delta(), Duration::from_millis(20));
+        assert_eq!(clock.elapsed(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,498 @@
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::{info, warn};
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    animations::word_chaos::Tool,
+    cli::CliArguments,
+    fonts::{FontMode, DEFAULT_FONT},
+    palette::PaletteConfig,
+    playlist::PlaylistConfig,
+    transition::TransitionKind,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Couldn't read config file {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Couldn't parse config file {path:?}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid value for '{key}' in config file {path:?}: {message}")]
+    Invalid {
+        path: PathBuf,
+        key: &'static str,
+        message: &'static str,
+    },
+}
+
+/// All tunables of the application.
+/// Every key is optional and falls back to its default value.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    /// The file this config has been read from, if any.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// The commandline arguments, which are applied again, whenever the config is reloaded.
+    #[serde(skip)]
+    cli: Option<CliArguments>,
+    /// Problems, that didn't stop the config from loading, e.g. unknown keys.
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+
+    pub general: General,
+    pub playlist: PlaylistConfig,
+    pub palette: PaletteConfig,
+    pub copter: CopterConfig,
+    pub word_chaos: WordChaosConfig,
+}
+
+/// Animation independent settings, which are used by the [State](crate::state::State).
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct General {
+    /// The word that's used by the wall and the copter.
+    pub word: String,
+    /// The percentile of the screen height, a single char should occupy.
+    pub relative_font_size: f32,
+    /// How long a single animation should run in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub animation_duration: Duration,
+    /// How long a transition between two animations should take in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub transition_duration: Duration,
+    /// The transition that's used, if a playlist entry doesn't specify one.
+    pub transition: TransitionKind,
+    /// Path to a wordlist. Either plain text with one word per line or a `.toml`/`.json` file.
+    pub wordlist: PathBuf,
+    /// Path to a ttf font or the name of a bundled font, e.g. `RobotoMono-Bold`.
+    pub font: PathBuf,
+    /// Whether each animation uses the configured font or another bundled font.
+    pub font_mode: FontMode,
+    /// A directory with GLSL sources, that replace the built-in shaders of the same name.
+    pub shaders: PathBuf,
+}
+
+impl Default for General {
+    fn default() -> Self {
+        General {
+            word: "ROFLCOPTER".to_string(),
+            relative_font_size: 60.0,
+            animation_duration: Duration::from_secs(80),
+            transition_duration: Duration::from_secs(2),
+            transition: TransitionKind::Random,
+            wordlist: PathBuf::from(DEFAULT_WORDLIST),
+            font: PathBuf::from(DEFAULT_FONT),
+            font_mode: FontMode::Fixed,
+            shaders: PathBuf::from("shaders"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CopterConfig {
+    /// A toml file with enemy kinds and waves.
+    /// Without it, there are endless waves of enemies, which use the settings below.
+    pub waves: PathBuf,
+    /// The enemy speed per second, relative to the window width.
+    pub enemy_speed: f32,
+    pub enemy_max_health: usize,
+    /// The amount of enemies that spawn in a single wave.
+    pub enemy_wave_size: usize,
+    /// The time between two enemy waves in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub enemy_wave_timeout: Duration,
+    /// The time between two shots in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub shot_timeout: Duration,
+    /// The chance of a killed enemy to drop a power-up, between 0 and 1.
+    pub power_up_chance: f32,
+    /// How long a dropped power-up can be collected in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub power_up_lifetime: Duration,
+    /// How long a collected weapon lasts in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub weapon_duration: Duration,
+    /// How many enemies may hit the copter, before a life is lost.
+    pub health: usize,
+    /// The amount of lives, before the game is over.
+    pub lives: usize,
+    /// How long the copter can't be hit after a hit in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub invulnerability: Duration,
+    /// How long the game over screen is shown, before the autopilot starts again in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub respawn_timeout: Duration,
+    /// Kills within this time in seconds increase the combo.
+    #[serde(deserialize_with = "seconds")]
+    pub combo_timeout: Duration,
+    /// The highest possible combo multiplier.
+    pub max_combo: usize,
+    /// Whether the copter makes room for the next animation, once a boss has been defeated.
+    pub end_after_boss: bool,
+    /// Where the high scores are stored. Defaults to the user's data directory.
+    pub high_scores: Option<PathBuf>,
+}
+
+impl Default for CopterConfig {
+    fn default() -> Self {
+        CopterConfig {
+            waves: PathBuf::from("./waves.toml"),
+            enemy_speed: 0.05,
+            enemy_max_health: 3,
+            enemy_wave_size: 12,
+            enemy_wave_timeout: Duration::from_secs(10),
+            shot_timeout: Duration::from_millis(300),
+            power_up_chance: 0.1,
+            power_up_lifetime: Duration::from_secs(10),
+            weapon_duration: Duration::from_secs(15),
+            health: 3,
+            lives: 3,
+            invulnerability: Duration::from_secs(2),
+            respawn_timeout: Duration::from_secs(5),
+            combo_timeout: Duration::from_millis(1500),
+            max_combo: 8,
+            end_after_boss: true,
+            high_scores: default_high_score_path(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WordChaosConfig {
+    /// The maximum amount of words on the screen.
+    pub word_limit: usize,
+    /// The time between two words spawned by the mouse in seconds.
+    #[serde(deserialize_with = "seconds")]
+    pub spawn_timeout: Duration,
+    /// Whether words bounce off each other, instead of only off the screen edges.
+    pub physics: bool,
+    /// The chance of two colliding words to merge back into a longer one, between 0 and 1.
+    pub merge_chance: f32,
+    /// What the left mouse button does: `spawn`, `attract`, `repel`, `vortex` or `freeze`.
+    /// The number keys switch between them.
+    pub tool: Tool,
+    /// The radius around the cursor, in which tools and right-drags affect words,
+    /// relative to the window height.
+    pub tool_radius: f32,
+    /// The acceleration of words at the cursor in window widths per second squared.
+    pub tool_strength: f32,
+    /// Whether a virtual cursor uses the tool instead of the mouse.
+    /// It never spawns words, but picks one of the other tools instead.
+    pub autopilot: bool,
+}
+
+impl Default for WordChaosConfig {
+    fn default() -> Self {
+        WordChaosConfig {
+            word_limit: 500,
+            spawn_timeout: Duration::from_millis(300),
+            physics: false,
+            merge_chance: 0.2,
+            tool: Tool::Spawn,
+            tool_radius: 0.2,
+            tool_strength: 0.5,
+            autopilot: false,
+        }
+    }
+}
+
+impl Config {
+    /// Read the config from the given path.
+    ///
+    /// If no path is given, we look for `roflcopter/config.toml` in the XDG config directory.
+    /// Only an explicitly given config file is required to exist.
+    pub fn read(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_config_path() {
+                Some(path) if path.exists() => path,
+                _ => {
+                    info!("No config file found, using defaults.");
+                    return Ok(Config::default());
+                }
+            },
+        };
+
+        let content = read_to_string(&path).map_err(|source| ConfigError::Io {
+            path: path.clone(),
+            source,
+        })?;
+
+        Config::from_toml(&path, &content)
+    }
+
+    /// Deserialize a config from its toml representation.
+    /// Unknown keys are ignored, but a warning with the key's location is logged.
+    pub fn from_toml(path: &Path, content: &str) -> Result<Config, ConfigError> {
+        let deserializer = toml::Deserializer::new(content);
+        let mut warnings = Vec::new();
+        let mut config: Config = serde_ignored::deserialize(deserializer, |key| {
+            let key = key.to_string();
+            warnings.push(match find_key_line(content, &key) {
+                Some(line) => format!("{}:{line}: Unknown config key '{key}'", path.display()),
+                None => format!("{}: Unknown config key '{key}'", path.display()),
+            });
+        })
+        .map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        config.path = Some(path.to_path_buf());
+        for warning in warnings.iter() {
+            warn!("{warning}");
+        }
+        config.warnings = warnings;
+
+        // The font size is the window height divided by this value.
+        let relative_font_size = config.general.relative_font_size;
+        if relative_font_size.is_nan() || relative_font_size <= 0.0 {
+            return Err(ConfigError::Invalid {
+                path: path.to_path_buf(),
+                key: "general.relative_font_size",
+                message: "must be greater than 0",
+            });
+        }
+        // The word is the fallback for the whole wordlist, so it has to be visible.
+        if config.general.word.trim().is_empty() {
+            return Err(ConfigError::Invalid {
+                path: path.to_path_buf(),
+                key: "general.word",
+                message: "must not be empty",
+            });
+        }
+
+        Ok(config)
+    }
+
+    /// Read the config file again.
+    /// Values that have been passed via the commandline still take precedence.
+    pub fn reload(&self) -> Result<Config, ConfigError> {
+        let mut config = Config::read(self.path.as_deref())?;
+        if let Some(args) = &self.cli {
+            config.merge_cli(args);
+        }
+
+        Ok(config)
+    }
+
+    /// Values that have been passed via the commandline take precedence over the config file.
+    pub fn merge_cli(&mut self, args: &CliArguments) {
+        self.cli = Some(args.clone());
+
+        if let Some(duration) = args.duration {
+            self.general.animation_duration = Duration::from_secs(duration);
+        }
+        if let Some(wordlist) = &args.wordlist {
+            self.general.wordlist = wordlist.clone();
+        }
+        if let Some(font) = &args.font {
+            self.general.font = font.clone();
+        }
+        if let Some(font_mode) = args.font_mode {
+            self.general.font_mode = font_mode;
+        }
+        if let Some(palette) = &args.palette {
+            self.palette.theme = palette.clone();
+        }
+    }
+}
+
+/// The wordlist, that's used if none is configured. It's fine, if it doesn't exist.
+pub const DEFAULT_WORDLIST: &str = "./wordlist.txt";
+
+/// The default location of the config file.
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("roflcopter").join("config.toml"))
+}
+
+/// The default location of the copter's high score table.
+pub fn default_high_score_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("roflcopter").join("highscores.toml"))
+}
+
+/// Deserialize a duration from a (fractional) amount of seconds.
+pub(crate) fn seconds<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let seconds = f64::deserialize(deserializer)?;
+    Duration::try_from_secs_f64(seconds).map_err(serde::de::Error::custom)
+}
+
+/// Find the (1-based) line of a dotted key such as `copter.enemy_speed`.
+///
+/// This is a simple line based search, which keeps track of the current table header
+/// and compares each key, including its table, with the searched key.
+/// Entries of arrays of tables are counted, as their index is part of the key,
+/// e.g. `playlist.entries.1.weight`.
+fn find_key_line(content: &str, key: &str) -> Option<usize> {
+    // Optional values show up as `?` in the key.
+    let key: Vec<&str> = key.split('.').filter(|part| *part != "?").collect();
+
+    let mut table: Vec<String> = Vec::new();
+    let mut array_lengths: HashMap<Vec<String>, usize> = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let header = if let Some(header) = line.strip_prefix("[[") {
+            let header = split_key(header.split("]]").next().unwrap_or_default());
+            let length = array_lengths.entry(header.clone()).or_default();
+            table = header.clone();
+            table.push(length.to_string());
+            *length += 1;
+            header
+        } else if let Some(header) = line.strip_prefix('[') {
+            table = split_key(header.split(']').next().unwrap_or_default());
+            table.clone()
+        } else if let Some((line_key, _)) = line.split_once('=') {
+            let full_key = table.iter().cloned().chain(split_key(line_key));
+            if full_key.eq(key.iter().map(|part| part.to_string())) {
+                return Some(index + 1);
+            }
+            continue;
+        } else {
+            continue;
+        };
+
+        // Unknown tables are reported by their header.
+        let matches = |parts: &[String]| parts.iter().map(String::as_str).eq(key.iter().copied());
+        if matches(&header) || matches(&table) {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+/// The parts of a dotted key without their quotes, e.g. `copter."enemy_speed"`.
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| {
+            part.trim()
+                .trim_matches(|c| c == '"' || c == '\'')
+                .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = r#"
+# A comment = with an equals sign
+speed = 1
+
+[copter]
+enemy_speed = 2
+"shot_speed" = 3
+
+[copter.boss]
+'health' = 4
+
+[unknown]
+key = 5
+
+[[playlist.entries]]
+animation = "wall"
+
+[[playlist.entries]]
+animation = "copter"
+wieght = 2
+"#;
+
+    #[test]
+    fn keys_are_found_in_their_table() {
+        assert_eq!(find_key_line(CONTENT, "speed"), Some(3));
+        assert_eq!(find_key_line(CONTENT, "copter.enemy_speed"), Some(6));
+        assert_eq!(find_key_line(CONTENT, "copter.boss.enemy_speed"), None);
+        assert_eq!(find_key_line(CONTENT, "missing"), None);
+    }
+
+    #[test]
+    fn quoted_keys_are_found() {
+        assert_eq!(find_key_line(CONTENT, "copter.shot_speed"), Some(7));
+        assert_eq!(find_key_line(CONTENT, "copter.boss.health"), Some(10));
+    }
+
+    #[test]
+    fn unknown_tables_are_found_by_their_header() {
+        assert_eq!(find_key_line(CONTENT, "copter.boss"), Some(9));
+        assert_eq!(find_key_line(CONTENT, "unknown"), Some(12));
+    }
+
+    #[test]
+    fn keys_in_arrays_of_tables_are_found() {
+        assert_eq!(
+            find_key_line(CONTENT, "playlist.entries.1.wieght"),
+            Some(20)
+        );
+        assert_eq!(find_key_line(CONTENT, "playlist.entries.0.wieght"), None);
+    }
+
+    #[test]
+    fn font_sizes_have_to_be_positive() {
+        let path = Path::new("config.toml");
+        for value in ["0", "-1.5", "nan"] {
+            let content = format!("[general]\nrelative_font_size = {value}");
+            assert!(matches!(
+                Config::from_toml(path, &content),
+                Err(ConfigError::Invalid { .. })
+            ));
+        }
+
+        let config = Config::from_toml(path, "[general]\nrelative_font_size = 40").unwrap();
+        assert_eq!(config.general.relative_font_size, 40.0);
+    }
+
+    #[test]
+    fn words_must_not_be_empty() {
+        let path = Path::new("config.toml");
+        for value in ["\"\"", "\"  \"", "\"\"\"\n\"\"\""] {
+            let content = format!("[general]\nword = {value}");
+            assert!(matches!(
+                Config::from_toml(path, &content),
+                Err(ConfigError::Invalid {
+                    key: "general.word",
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_reported_with_their_line() {
+        let config = Config::from_toml(Path::new("config.toml"), CONTENT).unwrap();
+
+        assert!(config
+            .warnings
+            .contains(&"config.toml:3: Unknown config key 'speed'".to_string()));
+        assert!(config
+            .warnings
+            .contains(&"config.toml:12: Unknown config key 'unknown'".to_string()));
+        assert!(
+            Config::from_toml(Path::new("config.toml"), "[general]\nword = \"LOL\"")
+                .unwrap()
+                .warnings
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use std::{
+    fs::create_dir_all,
+    io,
+    path::{Path, PathBuf},
+};
+
+use log::info;
+use macroquad::prelude::get_screen_data;
+
+/// Saves the rendered frames as a numbered sequence of PNG files.
+pub struct FrameExporter {
+    directory: PathBuf,
+    /// The total amount of frames that should be exported.
+    frames: u64,
+    /// The number of the next frame.
+    current: u64,
+}
+
+impl FrameExporter {
+    /// Create a new exporter, the target directory is created if it doesn't exist yet.
+    pub fn new(directory: &Path, frames: u64) -> io::Result<FrameExporter> {
+        create_dir_all(directory)?;
+
+        Ok(FrameExporter {
+            directory: directory.to_path_buf(),
+            frames,
+            current: 0,
+        })
+    }
+
+    /// Whether all frames have been exported.
+    pub fn is_done(&self) -> bool {
+        self.current >= self.frames
+    }
+
+    /// Save the current content of the screen as the next frame.
+    pub fn capture(&mut self) {
+        let mut image = get_screen_data();
+
+        // Semi-transparent text leaves traces in the alpha channel of the screen,
+        // which shouldn't end up in the exported image.
+        for pixel in image.get_image_data_mut() {
+            pixel[3] = 255;
+        }
+
+        // Pad the frame number, so the files are sorted correctly.
+        let digits = self.frames.to_string().len().max(6);
+        let path = self
+            .directory
+            .join(format!("frame_{:0digits$}.png", self.current));
+        image.export_png(&path.to_string_lossy());
+
+        self.current += 1;
+        info!("Exported frame {}/{}", self.current, self.frames);
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use macroquad::{
+    rand::RandGenerator,
+    text::{load_ttf_font_from_bytes, Font},
+};
+use serde::Deserialize;
+
+/// The font that's used, if no other font has been configured.
+pub const DEFAULT_FONT: &str = "RobotoMono-SemiBold";
+
+/// A font, that is compiled into the binary.
+pub struct BundledFont {
+    pub name: &'static str,
+    bytes: &'static [u8],
+}
+
+macro_rules! bundled {
+    ($($name:literal),* $(,)?) => {
+        [$(BundledFont {
+            name: $name,
+            bytes: include_bytes!(concat!("../fonts/", $name, ".ttf")),
+        }),*]
+    };
+}
+
+/// All weights and styles of RobotoMono, which are shipped in the `fonts` directory.
+pub const BUNDLED_FONTS: [BundledFont; 14] = bundled![
+    "RobotoMono-Thin",
+    "RobotoMono-ThinItalic",
+    "RobotoMono-ExtraLight",
+    "RobotoMono-ExtraLightItalic",
+    "RobotoMono-Light",
+    "RobotoMono-LightItalic",
+    "RobotoMono-Regular",
+    "RobotoMono-Italic",
+    "RobotoMono-Medium",
+    "RobotoMono-MediumItalic",
+    "RobotoMono-SemiBold",
+    "RobotoMono-SemiBoldItalic",
+    "RobotoMono-Bold",
+    "RobotoMono-BoldItalic",
+];
+
+/// How the font of an animation is chosen.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FontMode {
+    /// Always use the configured font.
+    #[default]
+    Fixed,
+    /// Go through all bundled fonts, one per animation.
+    Cycle,
+    /// Pick a random bundled font for each animation.
+    Random,
+}
+
+impl FontMode {
+    /// Pick the bundled font for the next animation, based on the current font.
+    /// Returns `None`, if the configured font should be used.
+    pub fn pick(self, current: &str, rng: &RandGenerator) -> Option<usize> {
+        let current = bundled_index(current);
+        match self {
+            FontMode::Fixed => None,
+            FontMode::Cycle => Some(current.map_or(0, |index| (index + 1) % BUNDLED_FONTS.len())),
+            FontMode::Random => {
+                match current {
+                    // Skip the current font, so there's always a visible change.
+                    Some(current) => {
+                        let index = rng.gen_range(0, BUNDLED_FONTS.len() - 1);
+                        Some(if index >= current { index + 1 } else { index })
+                    }
+                    None => Some(rng.gen_range(0, BUNDLED_FONTS.len())),
+                }
+            }
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FontError {
+    #[error("Couldn't read font {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Couldn't load font {path:?}: {message}")]
+    Load { path: PathBuf, message: String },
+    #[error("There's neither a font file {path:?} nor a bundled font of that name")]
+    Unknown { path: PathBuf },
+}
+
+/// Loads fonts and keeps the bundled ones around, as macroquad can't free fonts.
+#[derive(Default)]
+pub struct FontLibrary {
+    loaded: HashMap<&'static str, Font>,
+}
+
+impl FontLibrary {
+    pub fn new() -> FontLibrary {
+        FontLibrary::default()
+    }
+
+    /// Load a font by its path or the name of a bundled font.
+    ///
+    /// Font files take precedence. If there's no such file, but a bundled font with the same
+    /// name, e.g. `fonts/RobotoMono-Bold.ttf`, the bundled font is used.
+    /// Returns the font and its name.
+    pub fn load(&mut self, font: &Path) -> Result<(Font, String), FontError> {
+        if font.is_file() {
+            let bytes = fs::read(font).map_err(|source| FontError::Io {
+                path: font.to_path_buf(),
+                source,
+            })?;
+            let loaded = load_ttf_font_from_bytes(&bytes).map_err(|err| FontError::Load {
+                path: font.to_path_buf(),
+                message: err.to_string(),
+            })?;
+            let name = font
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            return Ok((loaded, name));
+        }
+
+        let bundled = [
+            font.to_str(),
+            font.file_stem().and_then(|stem| stem.to_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(bundled_index)
+        .ok_or_else(|| FontError::Unknown {
+            path: font.to_path_buf(),
+        })?;
+
+        Ok(self.bundled(bundled))
+    }
+
+    /// The bundled font with the given index in [BUNDLED_FONTS].
+    pub fn bundled(&mut self, index: usize) -> (Font, String) {
+        let bundled = &BUNDLED_FONTS[index % BUNDLED_FONTS.len()];
+        let font = *self.loaded.entry(bundled.name).or_insert_with(|| {
+            load_ttf_font_from_bytes(bundled.bytes)
+                .unwrap_or_else(|err| panic!("Bundled font {} is broken: {err}", bundled.name))
+        });
+
+        (font, bundled.name.to_string())
+    }
+
+    /// The font that's used as a fallback, if the configured one can't be loaded.
+    pub fn default_font(&mut self) -> (Font, String) {
+        let index = bundled_index(DEFAULT_FONT).expect("The default font is bundled");
+        self.bundled(index)
+    }
+}
+
+/// The index of a bundled font in [BUNDLED_FONTS].
+pub fn bundled_index(name: &str) -> Option<usize> {
+    BUNDLED_FONTS.iter().position(|font| font.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_bundled_fonts_can_be_found() {
+        for (index, font) in BUNDLED_FONTS.iter().enumerate() {
+            assert_eq!(bundled_index(font.name), Some(index));
+            assert!(!font.bytes.is_empty());
+        }
+        assert!(bundled_index(DEFAULT_FONT).is_some());
+    }
+
+    #[test]
+    fn cycle_goes_through_all_fonts() {
+        let rng = RandGenerator::new();
+        let mut current = DEFAULT_FONT;
+        let mut seen = Vec::new();
+        for _ in 0..BUNDLED_FONTS.len() {
+            let index = FontMode::Cycle.pick(current, &rng).unwrap();
+            current = BUNDLED_FONTS[index].name;
+            seen.push(index);
+        }
+
+        seen.sort_unstable();
+        assert_eq!(seen, (0..BUNDLED_FONTS.len()).collect::<Vec<_>>());
+        assert_eq!(FontMode::Cycle.pick("my-font.ttf", &rng), Some(0));
+        assert_eq!(FontMode::Fixed.pick(DEFAULT_FONT, &rng), None);
+    }
+
+    #[test]
+    fn random_always_changes_the_font() {
+        let rng = RandGenerator::new();
+        rng.srand(3);
+        for _ in 0..100 {
+            let index = FontMode::Random.pick(DEFAULT_FONT, &rng).unwrap();
+            assert!(index < BUNDLED_FONTS.len());
+            assert_ne!(BUNDLED_FONTS[index].name, DEFAULT_FONT);
+        }
+    }
+
+    #[test]
+    fn random_can_pick_every_font_after_a_custom_one() {
+        let rng = RandGenerator::new();
+        rng.srand(5);
+        let mut seen = vec![false; BUNDLED_FONTS.len()];
+        for _ in 0..500 {
+            seen[FontMode::Random.pick("my-font.ttf", &rng).unwrap()] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+    }
+
+    #[test]
+    fn unknown_fonts_are_rejected() {
+        let mut library = FontLibrary::new();
+        assert!(matches!(
+            library.load(Path::new("does/not/exist.ttf")),
+            Err(FontError::Unknown { .. })
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+
+/// A uniform grid for the broad phase of collision checks.
+///
+/// Entities are inserted with their index and bounds, which puts them into every cell they touch.
+/// Queries only return the entities of the cells around the queried area,
+/// so only those need a precise check.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> SpatialGrid {
+        SpatialGrid {
+            cell_size: cell_size.max(1.0),
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Remove all entities, e.g. before they're inserted again with their new positions.
+    /// The cells are kept, so their memory can be reused.
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, bounds: Rect) {
+        let (min, max) = self.cell_range(bounds);
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// All entities, whose cells overlap with the given area, sorted by their index.
+    pub fn query(&self, area: Rect) -> Vec<usize> {
+        let (min, max) = self.cell_range(area);
+        let mut found = Vec::new();
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    found.extend_from_slice(cell);
+                }
+            }
+        }
+
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    /// Each pair of entities, that share a cell, exactly once and with the lower index first.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for cell in self.cells.values() {
+            for (offset, first) in cell.iter().enumerate() {
+                for second in cell[offset + 1..].iter() {
+                    pairs.push(((*first).min(*second), (*first).max(*second)));
+                }
+            }
+        }
+
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+    }
+
+    /// The first and last cell, that are touched by an area.
+    fn cell_range(&self, area: Rect) -> ((i32, i32), (i32, i32)) {
+        let cell = |value: f32| (value / self.cell_size).floor() as i32;
+        (
+            (cell(area.x), cell(area.y)),
+            (cell(area.x + area.w), cell(area.y + area.h)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_only_return_nearby_entities() {
+        let mut grid = SpatialGrid::new(100.0);
+        grid.insert(0, Rect::new(10.0, 10.0, 20.0, 20.0));
+        grid.insert(1, Rect::new(500.0, 500.0, 20.0, 20.0));
+        // This one spans two cells.
+        grid.insert(2, Rect::new(90.0, 10.0, 20.0, 20.0));
+
+        assert_eq!(grid.query(Rect::new(0.0, 0.0, 50.0, 50.0)), vec![0, 2]);
+        assert_eq!(grid.query(Rect::new(150.0, 0.0, 10.0, 10.0)), vec![2]);
+        assert!(grid.query(Rect::new(-500.0, 0.0, 10.0, 10.0)).is_empty());
+
+        grid.clear();
+        assert!(grid.query(Rect::new(0.0, 0.0, 1000.0, 1000.0)).is_empty());
+    }
+
+    #[test]
+    fn pairs_are_unique() {
+        let mut grid = SpatialGrid::new(100.0);
+        grid.insert(3, Rect::new(90.0, 90.0, 20.0, 20.0));
+        grid.insert(1, Rect::new(95.0, 95.0, 20.0, 20.0));
+        grid.insert(2, Rect::new(900.0, 900.0, 20.0, 20.0));
+
+        assert_eq!(grid.pairs(), vec![(1, 3)]);
+    }
+}
@@ -1,59 +1,64 @@
-use std::time::Duration;
-
 use macroquad::{
     prelude::*,
-    rand::{gen_range, ChooseRandom},
+    rand::{ChooseRandom, RandGenerator},
 };
 
-use crate::state::State;
+use crate::simulation::Step;
 
+pub mod grid;
+pub mod obb;
+pub mod particles;
+pub mod rasterizer;
 pub mod structures;
+pub mod text;
 pub mod texture;
 pub mod vector;
 
+pub use grid::*;
+pub use obb::*;
+pub use particles::*;
+pub use rasterizer::*;
 pub use structures::*;
+pub use text::*;
 pub use texture::*;
 pub use vector::*;
 
-/// Helper, which returns macroquad's current delta frame time as std Duration.
-pub fn delta_duration() -> Duration {
-    let dt = (get_frame_time() * 1000.0 * 1000.0) as u64;
-    Duration::from_micros(dt)
-}
-
-/// Return a random color.
-pub fn random_color() -> Color {
-    Color::from_rgba(gen_range(0, 255), gen_range(0, 255), gen_range(0, 255), 255)
+/// Return a random color of the given palette.
+/// Falls back to white, if the palette is empty.
+pub fn random_color(rng: &RandGenerator, palette: &[Color]) -> Color {
+    palette.choose_with_state(rng).copied().unwrap_or(WHITE)
 }
 
-pub fn random_position_on_screen(state: &State) -> Vec2 {
+pub fn random_position_on_screen(step: &Step) -> Vec2 {
     Vec2::new(
-        gen_range(100.0, state.window_width - 100.0),
-        gen_range(100.0, state.window_height - 100.0),
+        step.rng.gen_range(100.0, step.window_width - 100.0),
+        step.rng.gen_range(100.0, step.window_height - 100.0),
     )
 }
 
 /// Return a random position at the edges of the screen.
 /// The position can be offset by a given amount.
-pub fn random_position_outside_screen(state: &State, offset: f32) -> Vec2 {
+pub fn random_position_outside_screen(step: &Step, offset: f32) -> Vec2 {
     let mut position = Vec2::new(
-        gen_range(0.0, state.window_width),
-        gen_range(0.0, state.window_height),
+        step.rng.gen_range(0.0, step.window_width),
+        step.rng.gen_range(0.0, step.window_height),
     );
 
-    let directions = vec![
+    let directions = [
         Direction::Top,
         Direction::Bottom,
         Direction::Left,
         Direction::Right,
     ];
-    let direction = directions.choose().expect("Failed to get random direction");
+    let direction = directions
+        .choose_with_state(step.rng)
+        .expect("Failed to get random direction");
 
     match direction {
         Direction::Top => position.y = -offset,
-        Direction::Bottom => position.y = state.window_height + offset,
+        Direction::Bottom => position.y = step.window_height + offset,
         Direction::Left => position.x = -offset,
-        Direction::Right => position.x = state.window_width + offset,
+        Direction::Right => position.x = step.window_width + offset,
     }
 
     position
@@ -0,0 +1,124 @@
+use macroquad::prelude::*;
+
+/// An oriented bounding box, e.g. of a rotated texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Obb {
+    pub center: Vec2,
+    pub half_size: Vec2,
+    /// The rotation around the center in radians.
+    pub angle: f32,
+}
+
+impl Obb {
+    pub fn new(center: Vec2, size: Vec2, angle: f32) -> Obb {
+        Obb {
+            center,
+            half_size: size / 2.0,
+            angle,
+        }
+    }
+
+    /// The box of a texture, that's drawn at the given position with the given rotation.
+    /// Macroquad rotates textures around their center.
+    pub fn from_texture(position: Vec2, size: Vec2, rotation: f32) -> Obb {
+        Obb::new(position + size / 2.0, size, rotation)
+    }
+
+    /// The directions of the box' sides.
+    pub fn axes(&self) -> [Vec2; 2] {
+        let x = Vec2::new(self.angle.cos(), self.angle.sin());
+        [x, x.perp()]
+    }
+
+    pub fn corners(&self) -> [Vec2; 4] {
+        let [x, y] = self.axes();
+        let (x, y) = (x * self.half_size.x, y * self.half_size.y);
+        [
+            self.center - x - y,
+            self.center + x - y,
+            self.center + x + y,
+            self.center - x + y,
+        ]
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let relative = point - self.center;
+        let [x, y] = self.axes();
+        relative.dot(x).abs() <= self.half_size.x && relative.dot(y).abs() <= self.half_size.y
+    }
+
+    /// Check for an overlap with the separating axis theorem.
+    /// Two boxes don't overlap, if there's a side of either box, on whose axis they don't overlap.
+    pub fn intersects(&self, other: &Obb) -> bool {
+        let (corners, other_corners) = (self.corners(), other.corners());
+        self.axes().iter().chain(other.axes().iter()).all(|axis| {
+            let (min, max) = project(&corners, *axis);
+            let (other_min, other_max) = project(&other_corners, *axis);
+            min <= other_max && other_min <= max
+        })
+    }
+
+    /// The axis aligned box around this box, which is used for the broad phase.
+    pub fn bounds(&self) -> Rect {
+        let corners = self.corners();
+        let min = corners
+            .iter()
+            .fold(corners[0], |min, corner| min.min(*corner));
+        let max = corners
+            .iter()
+            .fold(corners[0], |max, corner| max.max(*corner));
+
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+}
+
+/// The range covered by the corners along an axis.
+fn project(corners: &[Vec2; 4], axis: Vec2) -> (f32, f32) {
+    corners
+        .iter()
+        .map(|corner| corner.dot(axis))
+        .fold((f32::MAX, f32::MIN), |(min, max), value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn rotated_boxes_contain_points() {
+        // A long box, that has been turned upright.
+        let obb = Obb::new(Vec2::ZERO, Vec2::new(100.0, 10.0), PI / 2.0);
+
+        assert!(obb.contains(Vec2::new(0.0, 45.0)));
+        assert!(!obb.contains(Vec2::new(45.0, 0.0)));
+
+        let bounds = obb.bounds();
+        assert!((bounds.w - 10.0).abs() < 0.001);
+        assert!((bounds.h - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn boxes_intersect_by_their_shape() {
+        let diagonal = Obb::new(Vec2::ZERO, Vec2::new(100.0, 10.0), PI / 4.0);
+        let on_the_line = Obb::new(Vec2::new(30.0, 30.0), Vec2::new(10.0, 10.0), 0.0);
+        // This box overlaps the bounds of the diagonal box, but not the box itself.
+        let in_the_corner = Obb::new(Vec2::new(30.0, -30.0), Vec2::new(10.0, 10.0), 0.0);
+
+        assert!(diagonal.intersects(&on_the_line));
+        assert!(on_the_line.intersects(&diagonal));
+        assert!(diagonal.bounds().overlaps(&in_the_corner.bounds()));
+        assert!(!diagonal.intersects(&in_the_corner));
+    }
+
+    #[test]
+    fn texture_boxes_turn_around_their_center() {
+        let obb = Obb::from_texture(Vec2::new(10.0, 10.0), Vec2::new(40.0, 20.0), PI);
+
+        assert_eq!(obb.center, Vec2::new(30.0, 20.0));
+        assert!(obb.contains(Vec2::new(12.0, 12.0)));
+    }
+}
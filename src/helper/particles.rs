@@ -0,0 +1,356 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use macroquad::{prelude::*, rand::ChooseRandom};
+
+use crate::simulation::Step;
+
+/// What a particle looks like.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParticleShape {
+    /// A filled square, whose size is given in pixels.
+    Quad,
+    /// A texture of the list, that's passed to [ParticleSystem::draw], scaled by the particle's size.
+    /// The source area is relative to the texture's size, e.g. to only draw a single letter of a word.
+    Glyph {
+        texture: usize,
+        source: Option<Rect>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub shape: ParticleShape,
+    /// The middle of the particle.
+    pub position: Vec2,
+    /// The velocity in pixels per second.
+    pub velocity: Vec2,
+    pub angle: f32,
+    /// The rotation in radians per second.
+    pub spin: f32,
+    pub size: f32,
+    /// How strongly gravity pulls on this particle. Negative weights make it rise, like smoke.
+    pub weight: f32,
+    pub color: Color,
+    /// The color at the end of the particle's life.
+    pub fade_to: Color,
+    pub age: Duration,
+    pub lifetime: Duration,
+}
+
+impl Particle {
+    pub fn new(
+        shape: ParticleShape,
+        position: Vec2,
+        velocity: Vec2,
+        lifetime: Duration,
+    ) -> Particle {
+        Particle {
+            shape,
+            position,
+            velocity,
+            angle: 0.0,
+            spin: 0.0,
+            size: 1.0,
+            weight: 1.0,
+            color: WHITE,
+            fade_to: Color::new(1.0, 1.0, 1.0, 0.0),
+            age: Duration::from_secs(0),
+            lifetime,
+        }
+    }
+
+    /// How much of its life the particle has already lived, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        (self.age.as_secs_f32() / self.lifetime.as_secs_f32().max(f32::EPSILON)).min(1.0)
+    }
+
+    /// The current color, on its way from the start to the end color.
+    pub fn current_color(&self) -> Color {
+        let start = self.color.to_vec();
+        Color::from_vec(start.lerp(self.fade_to.to_vec(), self.progress()))
+    }
+}
+
+/// Spawns particles with random properties, either all at once or continuously.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+    /// Each particle gets a random one of these shapes.
+    pub shapes: Vec<ParticleShape>,
+    /// The amount of particles per second of a continuous emitter.
+    pub rate: f32,
+    /// The direction in radians, in which particles are emitted.
+    pub direction: f32,
+    /// Particles fly in a random direction up to half of this angle away from the direction.
+    pub spread: f32,
+    /// The speed range per second, relative to the window width.
+    pub speed: (f32, f32),
+    pub lifetime: (Duration, Duration),
+    /// The size range of the particles. Quads are sized relative to the glyph width.
+    pub size: (f32, f32),
+    /// The maximum rotation in radians per second in either direction.
+    pub spin: f32,
+    pub weight: f32,
+    /// Each particle starts with a random one of these colors.
+    pub colors: Vec<Color>,
+    pub fade_to: Color,
+    /// Partial particles of a continuous emitter, which are carried over to the next step.
+    pub pending: f32,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Emitter {
+            shapes: vec![ParticleShape::Quad],
+            rate: 0.0,
+            direction: 0.0,
+            spread: TAU,
+            speed: (0.05, 0.1),
+            lifetime: (Duration::from_millis(500), Duration::from_secs(1)),
+            size: (0.2, 0.4),
+            spin: 0.0,
+            weight: 1.0,
+            colors: vec![WHITE],
+            fade_to: Color::new(1.0, 1.0, 1.0, 0.0),
+            pending: 0.0,
+        }
+    }
+}
+
+impl Emitter {
+    /// Create a single random particle at the given position.
+    pub fn particle(&self, step: &Step, position: Vec2) -> Particle {
+        let rng = step.rng;
+        let shape = self
+            .shapes
+            .choose_with_state(rng)
+            .copied()
+            .unwrap_or(ParticleShape::Quad);
+        let angle = self.direction + random_between(step, -self.spread / 2.0, self.spread / 2.0);
+        let speed = random_between(step, self.speed.0, self.speed.1) * step.window_width;
+        let lifetime = random_between(
+            step,
+            self.lifetime.0.as_secs_f32(),
+            self.lifetime.1.as_secs_f32(),
+        );
+        let mut size = random_between(step, self.size.0, self.size.1);
+        if shape == ParticleShape::Quad {
+            size *= step.glyph.x;
+        }
+
+        Particle {
+            angle: random_between(step, 0.0, TAU),
+            spin: random_between(step, -self.spin, self.spin),
+            size,
+            weight: self.weight,
+            color: self.colors.choose_with_state(rng).copied().unwrap_or(WHITE),
+            fade_to: self.fade_to,
+            ..Particle::new(
+                shape,
+                position,
+                Vec2::new(angle.cos(), angle.sin()) * speed,
+                Duration::from_secs_f32(lifetime),
+            )
+        }
+    }
+
+    /// Spawn a bunch of particles at once, e.g. for an explosion.
+    pub fn burst(&self, system: &mut ParticleSystem, step: &Step, position: Vec2, count: usize) {
+        for _ in 0..count {
+            system.spawn(self.particle(step, position));
+        }
+    }
+
+    /// Spawn particles with the emitter's rate, e.g. for a trail of smoke.
+    pub fn emit(&mut self, system: &mut ParticleSystem, step: &Step, position: Vec2) {
+        self.pending += self.rate * step.delta_secs();
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            system.spawn(self.particle(step, position));
+        }
+    }
+}
+
+/// A random value in the range. Emitters may use fixed values, so `low` is used as is, if the range is empty.
+fn random_between(step: &Step, low: f32, high: f32) -> f32 {
+    if high > low {
+        step.rng.gen_range(low, high)
+    } else {
+        low
+    }
+}
+
+/// All particles of an animation.
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+    pub particles: Vec<Particle>,
+    /// The downward acceleration per second squared, relative to the window height.
+    pub gravity: f32,
+    /// New particles are dropped, once there are this many.
+    limit: usize,
+}
+
+impl ParticleSystem {
+    pub fn new(gravity: f32, limit: usize) -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::new(),
+            gravity,
+            limit,
+        }
+    }
+
+    pub fn spawn(&mut self, particle: Particle) {
+        if self.particles.len() < self.limit {
+            self.particles.push(particle);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Move all particles and remove the ones, whose life is over.
+    pub fn update(&mut self, step: &Step) {
+        let dt = step.delta_secs();
+        let gravity = Vec2::new(0.0, self.gravity * step.window_height);
+        for particle in self.particles.iter_mut() {
+            particle.age += step.delta;
+            particle.velocity += gravity * particle.weight * dt;
+            particle.position += particle.velocity * dt;
+            particle.angle += particle.spin * dt;
+        }
+
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Draw all particles. Glyphs are looked up in the given textures.
+    pub fn draw(&self, textures: &[Texture2D]) {
+        for particle in self.particles.iter() {
+            let color = particle.current_color();
+            match particle.shape {
+                ParticleShape::Quad => draw_rectangle_ex(
+                    particle.position.x,
+                    particle.position.y,
+                    particle.size,
+                    particle.size,
+                    DrawRectangleParams {
+                        offset: Vec2::new(0.5, 0.5),
+                        rotation: particle.angle,
+                        color,
+                    },
+                ),
+                ParticleShape::Glyph { texture, source } => {
+                    let Some(texture) = textures.get(texture) else {
+                        continue;
+                    };
+                    let texture_size = Vec2::new(texture.width(), texture.height());
+                    let source = source.map(|source| {
+                        Rect::new(
+                            source.x * texture_size.x,
+                            source.y * texture_size.y,
+                            source.w * texture_size.x,
+                            source.h * texture_size.y,
+                        )
+                    });
+                    let size = source.map_or(texture_size, |source| source.size()) * particle.size;
+                    let position = particle.position - size / 2.0;
+                    draw_texture_ex(
+                        *texture,
+                        position.x,
+                        position.y,
+                        color,
+                        DrawTextureParams {
+                            source,
+                            dest_size: Some(size),
+                            rotation: particle.angle,
+                            flip_y: true,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::rand::RandGenerator;
+
+    use super::*;
+    use crate::input::Input;
+
+    fn step<'a>(input: &'a Input, rng: &'a RandGenerator) -> Step<'a> {
+        Step {
+            input,
+            delta: Duration::from_millis(100),
+            elapsed: Duration::from_secs(1),
+            window_width: 1000.0,
+            window_height: 500.0,
+            glyph: Vec2::new(10.0, 20.0),
+            rng,
+        }
+    }
+
+    #[test]
+    fn particles_fall_and_fade() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let step = step(&input, &rng);
+        let mut system = ParticleSystem::new(1.0, 10);
+        let mut smoke = Particle::new(
+            ParticleShape::Quad,
+            Vec2::ZERO,
+            Vec2::ZERO,
+            Duration::from_millis(400),
+        );
+        smoke.weight = -0.5;
+        system.spawn(Particle::new(
+            ParticleShape::Quad,
+            Vec2::ZERO,
+            Vec2::ZERO,
+            Duration::from_millis(150),
+        ));
+        system.spawn(smoke);
+
+        system.update(&step);
+        assert!(system.particles[0].position.y > 0.0);
+        assert!(system.particles[1].position.y < 0.0);
+        assert!((system.particles[1].current_color().a - 0.75).abs() < 0.001);
+
+        // The first particle's life is over.
+        system.update(&step);
+        assert_eq!(system.particles.len(), 1);
+    }
+
+    #[test]
+    fn emitters_keep_their_rate() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let step = step(&input, &rng);
+        let mut system = ParticleSystem::new(0.0, 100);
+        let mut emitter = Emitter {
+            rate: 25.0,
+            ..Default::default()
+        };
+
+        // 2.5 particles per step.
+        for _ in 0..4 {
+            emitter.emit(&mut system, &step, Vec2::ZERO);
+        }
+        assert_eq!(system.particles.len(), 10);
+
+        // Quads are sized relative to the glyph width.
+        let size = system.particles[0].size;
+        assert!((2.0..=4.0).contains(&size));
+    }
+
+    #[test]
+    fn systems_are_limited() {
+        let (input, rng) = (Input::default(), RandGenerator::new());
+        let step = step(&input, &rng);
+        let mut system = ParticleSystem::new(0.0, 5);
+
+        Emitter::default().burst(&mut system, &step, Vec2::ZERO, 20);
+
+        assert_eq!(system.particles.len(), 5);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use macroquad::prelude::*;
+
+use super::{graphemes, render_target_camera};
+
+/// The amount of glyphs in a single row of an atlas.
+const ATLAS_COLUMNS: usize = 16;
+/// The amount of rows a new atlas starts with. Atlases grow, once they're full.
+const ATLAS_ROWS: usize = 4;
+
+/// All glyphs of an atlas share the same font, size and color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct AtlasKey {
+    /// The index of the font in [TextRasterizer::fonts], as fonts can't be hashed.
+    font: usize,
+    size: u16,
+    color: [u8; 4],
+}
+
+/// A render target that contains every glyph, that has been requested so far.
+#[derive(Debug)]
+struct GlyphAtlas {
+    target: RenderTarget,
+    /// The size of a single cell.
+    /// Cells are larger than a glyph, so glyphs with overhangs aren't cut off.
+    cell: Vec2,
+    /// The position of a glyph's origin inside of its cell.
+    origin: Vec2,
+    /// The cell of each grapheme.
+    glyphs: HashMap<String, usize>,
+}
+
+impl GlyphAtlas {
+    fn new(font: Font, size: u16) -> GlyphAtlas {
+        let dimensions = measure_text("j", Some(font), size, 1.0);
+        let cell = Vec2::new(dimensions.width * 2.0, dimensions.height * 2.0).ceil();
+
+        GlyphAtlas {
+            target: transparent_render_target(
+                cell * Vec2::new(ATLAS_COLUMNS as f32, ATLAS_ROWS as f32),
+            ),
+            cell,
+            origin: Vec2::new(dimensions.width / 2.0, dimensions.height * 1.5).round(),
+            glyphs: HashMap::new(),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        ATLAS_COLUMNS * (self.target.texture.height() / self.cell.y) as usize
+    }
+
+    /// The top left corner of the given cell.
+    fn cell_position(&self, index: usize) -> Vec2 {
+        Vec2::new(
+            (index % ATLAS_COLUMNS) as f32 * self.cell.x,
+            (index / ATLAS_COLUMNS) as f32 * self.cell.y,
+        )
+    }
+
+    /// The area of a cell inside of the atlas texture.
+    /// Render targets are stored upside down, which is why the rect is mirrored.
+    fn source(&self, index: usize) -> Rect {
+        let position = self.cell_position(index);
+        Rect::new(
+            position.x,
+            self.target.texture.height() - position.y - self.cell.y,
+            self.cell.x,
+            self.cell.y,
+        )
+    }
+
+    /// Draw the glyph into the next free cell, if it isn't part of the atlas yet.
+    fn insert(&mut self, font: Font, size: u16, color: Color, grapheme: &str) {
+        if self.glyphs.contains_key(grapheme) {
+            return;
+        }
+        if self.glyphs.len() == self.capacity() {
+            self.grow();
+        }
+
+        let index = self.glyphs.len();
+        let position = self.cell_position(index) + self.origin;
+        let size_px = Vec2::new(self.target.texture.width(), self.target.texture.height());
+
+        push_camera_state();
+        set_camera(&render_target_camera(self.target, size_px.x, size_px.y));
+        draw_text_ex(
+            grapheme,
+            position.x,
+            position.y,
+            TextParams {
+                font,
+                font_size: size,
+                font_scale: 1.0,
+                color,
+                ..Default::default()
+            },
+        );
+        pop_camera_state();
+
+        self.glyphs.insert(grapheme.to_string(), index);
+    }
+
+    /// Double the amount of rows and copy all existing glyphs over.
+    fn grow(&mut self) {
+        let old = self.target;
+        let size = Vec2::new(old.texture.width(), old.texture.height() * 2.0);
+        self.target = transparent_render_target(size);
+
+        push_camera_state();
+        set_camera(&render_target_camera(self.target, size.x, size.y));
+        draw_texture_ex(
+            old.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                flip_y: true,
+                ..Default::default()
+            },
+        );
+        pop_camera_state();
+
+        old.delete();
+    }
+}
+
+/// Renders text into textures without touching the screen.
+///
+/// Every glyph is only drawn once per font, size and color into an atlas.
+/// Texts are then composed from these atlases on an offscreen render target,
+/// so they can be larger than the window.
+#[derive(Debug, Default)]
+pub struct TextRasterizer {
+    fonts: RefCell<Vec<Font>>,
+    atlases: RefCell<HashMap<AtlasKey, GlyphAtlas>>,
+}
+
+impl TextRasterizer {
+    pub fn new() -> TextRasterizer {
+        TextRasterizer::default()
+    }
+
+    /// Render a (multi-line) text into a new texture.
+    ///
+    /// The colors are applied per grapheme of a line and repeat for long lines.
+    /// If no colors are given, the text is white.
+    /// Just like any other render target, the texture needs to be drawn with `flip_y`.
+    pub fn rasterize(
+        &self,
+        font: Font,
+        text: &str,
+        font_size: u16,
+        colors: Option<&[Color]>,
+    ) -> Texture2D {
+        let dimensions = measure_text("j", Some(font), font_size, 1.0);
+        let font = self.font_index(font);
+
+        // Lay out all glyphs first, as adding glyphs to an atlas switches the render target.
+        let mut glyphs = Vec::new();
+        let mut lines = 0;
+        let mut max_x: f32 = 0.0;
+        for (line_index, line) in text.lines().enumerate() {
+            lines += 1;
+            // The first baseline is one line below the top, just like with `draw_text`.
+            let baseline = dimensions.height * (line_index + 1) as f32;
+            let line = graphemes(line);
+            for (index, grapheme) in line.iter().enumerate() {
+                if grapheme.trim().is_empty() {
+                    continue;
+                }
+                let color = match colors {
+                    Some(colors) if !colors.is_empty() => colors[index % colors.len()],
+                    _ => WHITE,
+                };
+                let key = AtlasKey {
+                    font,
+                    size: font_size,
+                    color: color.into(),
+                };
+                let origin = Vec2::new(dimensions.width * index as f32, baseline);
+                glyphs.push((key, *grapheme, origin));
+            }
+            max_x = max_x.max(dimensions.width * line.len() as f32);
+        }
+
+        let mut atlases = self.atlases.borrow_mut();
+        let fonts = self.fonts.borrow();
+        for (key, grapheme, _) in glyphs.iter() {
+            let atlas = atlases
+                .entry(*key)
+                .or_insert_with(|| GlyphAtlas::new(fonts[key.font], key.size));
+            atlas.insert(fonts[key.font], key.size, key.color.into(), grapheme);
+        }
+
+        // Leave some space below the last line for descenders.
+        let size = Vec2::new(max_x, dimensions.height * (lines + 1) as f32)
+            .ceil()
+            .max(Vec2::ONE);
+        let target = transparent_render_target(size);
+
+        push_camera_state();
+        set_camera(&render_target_camera(target, size.x, size.y));
+        for (key, grapheme, origin) in glyphs.iter() {
+            let atlas = &atlases[key];
+            let index = atlas.glyphs[*grapheme];
+            let position = *origin - atlas.origin;
+            draw_texture_ex(
+                atlas.target.texture,
+                position.x,
+                position.y,
+                WHITE,
+                DrawTextureParams {
+                    source: Some(atlas.source(index)),
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+        }
+        pop_camera_state();
+
+        // Copy the text into a plain texture, so the render target can be freed right away.
+        let texture = Texture2D::from_image(&target.texture.get_texture_data());
+        target.delete();
+
+        texture
+    }
+
+    /// Drop all atlases, e.g. after the font size changed.
+    pub fn clear(&self) {
+        for (_, atlas) in self.atlases.borrow_mut().drain() {
+            atlas.target.delete();
+        }
+    }
+
+    fn font_index(&self, font: Font) -> usize {
+        let mut fonts = self.fonts.borrow_mut();
+        match fonts.iter().position(|known| *known == font) {
+            Some(index) => index,
+            None => {
+                fonts.push(font);
+                fonts.len() - 1
+            }
+        }
+    }
+}
+
+/// Create a render target of the given size, which is fully transparent.
+fn transparent_render_target(size: Vec2) -> RenderTarget {
+    let target = render_target(size.x as u32, size.y as u32);
+    target.texture.set_filter(FilterMode::Nearest);
+
+    push_camera_state();
+    set_camera(&render_target_camera(target, size.x, size.y));
+    clear_background(Color::new(0.0, 0.0, 0.0, 0.0));
+    pop_camera_state();
+
+    target
+}
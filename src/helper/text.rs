@@ -0,0 +1,69 @@
+use macroquad::prelude::{Color, Vec2};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Split a text into its graphemes, which are the characters as a user perceives them.
+/// A single grapheme may consist of several chars, e.g. umlauts with combining marks or emoji.
+pub fn graphemes(text: &str) -> Vec<&str> {
+    text.graphemes(true).collect()
+}
+
+/// The amount of graphemes of a text.
+/// Use this instead of `len()`, which counts bytes.
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// The width of a (multi-line) text in graphemes, which is the length of its longest line.
+pub fn grapheme_columns(text: &str) -> usize {
+    text.lines().map(grapheme_count).max().unwrap_or(0)
+}
+
+/// The size of a (multi-line) text's texture, given the size of a single glyph.
+/// Just like the rasterizer, this leaves an additional line for descenders.
+pub fn text_size(text: &str, glyph: Vec2) -> Vec2 {
+    let rows = text.lines().count();
+    Vec2::new(grapheme_columns(text) as f32, (rows + 1) as f32) * glyph
+}
+
+/// Repeat the palette, until there's a color for each of the `length` graphemes.
+pub fn cycle_colors(palette: &[Color], length: usize) -> Vec<Color> {
+    if palette.is_empty() {
+        return Vec::new();
+    }
+
+    palette.iter().cycle().take(length).copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graphemes_of_multilingual_words() {
+        assert_eq!(graphemes("Käse"), vec!["K", "ä", "s", "e"]);
+        // An `a` with a combining diaeresis is still a single grapheme.
+        assert_eq!(grapheme_count("Ka\u{308}se"), 4);
+        assert_eq!(grapheme_count("東京"), 2);
+        assert_eq!(graphemes("👍🏽ok"), vec!["👍🏽", "o", "k"]);
+        assert_eq!(grapheme_columns("LOL\nROFL\nÄ"), 4);
+        assert_eq!(
+            text_size("LOL\nROFL", Vec2::new(10.0, 20.0)),
+            Vec2::new(40.0, 60.0)
+        );
+    }
+
+    #[test]
+    fn colors_cycle_for_long_words() {
+        let palette = [
+            Color::new(1.0, 0.0, 0.0, 1.0),
+            Color::new(0.0, 1.0, 0.0, 1.0),
+        ];
+        let colors = cycle_colors(&palette, 5);
+
+        assert_eq!(colors.len(), 5);
+        assert_eq!(colors[0], palette[0]);
+        assert_eq!(colors[3], palette[1]);
+        assert_eq!(colors[4], palette[0]);
+        assert!(cycle_colors(&[], 3).is_empty());
+    }
+}
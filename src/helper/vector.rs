@@ -1,9 +1,9 @@
 use std::f32::consts::PI;
 
-use macroquad::{prelude::Vec2, rand::gen_range};
+use macroquad::{prelude::Vec2, rand::RandGenerator};
 
 use super::structures::*;
-use crate::state::State;
+use crate::simulation::Step;
 
 pub fn side(src: &Vec2, dest: &Vec2) -> Side {
     if src.x > dest.x {
@@ -27,18 +27,18 @@ pub fn rotate_vec2(vec: Vec2, angle: f32) -> Vec2 {
 }
 
 /// Create a new vector with a random direction and a given length.
-pub fn random_vector_with_lenght(length: f32) -> Vec2 {
+pub fn random_vector_with_lenght(rng: &RandGenerator, length: f32) -> Vec2 {
     let vec = Vec2::new(length, 0.0);
-    rotate_vec2(vec, gen_range(0.0, PI * 2.0))
+    rotate_vec2(vec, rng.gen_range(0.0, PI * 2.0))
 }
 
-pub fn outside_screen(state: &State, position: Vec2) -> Option<Direction> {
+pub fn outside_screen(step: &Step, position: Vec2) -> Option<Direction> {
     // Check collisions on all sides
-    if position.x >= state.window_width {
+    if position.x >= step.window_width {
         return Some(Direction::Right);
     } else if position.x <= -0.0 {
         return Some(Direction::Left);
-    } else if position.y >= state.window_height {
+    } else if position.y >= step.window_height {
         return Some(Direction::Bottom);
     } else if position.y <= -0.0 {
         return Some(Direction::Top);
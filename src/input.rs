@@ -0,0 +1,86 @@
+use macroquad::prelude::*;
+
+/// All keys that animations may react to.
+/// Global hotkeys are handled by the [State](crate::state::State) instead.
+const TRACKED_KEYS: [KeyCode; 17] = [
+    KeyCode::W,
+    KeyCode::A,
+    KeyCode::S,
+    KeyCode::D,
+    KeyCode::Up,
+    KeyCode::Left,
+    KeyCode::Down,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::M,
+    KeyCode::Backspace,
+    KeyCode::Enter,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+];
+
+/// A snapshot of the user input for the current frame.
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    pub mouse_position: Vec2,
+    /// Whether the mouse has been moved since the last frame.
+    pub mouse_moved: bool,
+    pub mouse_left_down: bool,
+    pub mouse_right_down: bool,
+    /// All tracked keys, that are currently held down.
+    pub keys_down: Vec<KeyCode>,
+    /// All tracked keys, that have been pressed during this frame.
+    pub keys_pressed: Vec<KeyCode>,
+    /// All characters, that have been typed during this frame.
+    pub chars: Vec<char>,
+}
+
+impl Input {
+    /// Capture the current input from macroquad.
+    /// The previous snapshot is needed to detect mouse movement.
+    pub fn capture(previous: &Input) -> Input {
+        let mouse_position = Vec2::from(mouse_position());
+
+        Input {
+            mouse_position,
+            mouse_moved: mouse_position != previous.mouse_position,
+            mouse_left_down: is_mouse_button_down(MouseButton::Left),
+            mouse_right_down: is_mouse_button_down(MouseButton::Right),
+            keys_down: TRACKED_KEYS
+                .into_iter()
+                .filter(|key| is_key_down(*key))
+                .collect(),
+            keys_pressed: TRACKED_KEYS
+                .into_iter()
+                .filter(|key| is_key_pressed(*key))
+                .collect(),
+            chars: std::iter::from_fn(get_char_pressed).collect(),
+        }
+    }
+
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// The direction of WASD or the arrow keys.
+    /// Each axis is either -1, 0 or 1, so the vector isn't normalized.
+    pub fn steering(&self) -> Vec2 {
+        let axis = |negative: [KeyCode; 2], positive: [KeyCode; 2]| {
+            let negative = negative.iter().any(|key| self.is_key_down(*key)) as i8;
+            let positive = positive.iter().any(|key| self.is_key_down(*key)) as i8;
+            (positive - negative) as f32
+        };
+
+        Vec2::new(
+            axis([KeyCode::A, KeyCode::Left], [KeyCode::D, KeyCode::Right]),
+            axis([KeyCode::W, KeyCode::Up], [KeyCode::S, KeyCode::Down]),
+        )
+    }
+}
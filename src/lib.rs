@@ -1,5 +1,17 @@
 pub mod animations;
-mod color;
+pub mod cli;
+pub mod clock;
+pub mod config;
+pub mod export;
+pub mod fonts;
 pub mod helper;
+pub mod input;
+pub mod palette;
+pub mod playlist;
+pub mod rng;
 mod shaders;
+pub mod simulation;
 pub mod state;
+pub mod transition;
+pub mod watcher;
+pub mod wordlist;
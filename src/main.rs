@@ -1,81 +1,159 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use macroquad::prelude::*;
-use simplelog::{Config, LevelFilter, SimpleLogger};
+use clap::Parser;
+use macroquad::{prelude::*, Window};
+use simplelog::{Config as LogConfig, LevelFilter, SimpleLogger};
 
 use roflcopter_lib::animations::*;
+use roflcopter_lib::cli::CliArguments;
+use roflcopter_lib::clock::Clock;
+use roflcopter_lib::config::Config;
+use roflcopter_lib::export::FrameExporter;
+use roflcopter_lib::palette::Palette;
+use roflcopter_lib::rng::RngStreams;
 use roflcopter_lib::state::State;
+use roflcopter_lib::wordlist::Wordlist;
 
-fn window_conf() -> Conf {
-    Conf {
-        window_title: "Roflcopter".to_owned(),
-        fullscreen: true,
-        ..Default::default()
+fn window_conf(args: &CliArguments) -> Conf {
+    match args.windowed {
+        Some(size) => Conf {
+            window_title: "Roflcopter".to_owned(),
+            window_width: size.width,
+            window_height: size.height,
+            fullscreen: false,
+            ..Default::default()
+        },
+        None => Conf {
+            window_title: "Roflcopter".to_owned(),
+            fullscreen: true,
+            ..Default::default()
+        },
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
+fn main() {
+    let args = CliArguments::parse();
+
+    setup(args.verbose);
+
+    let mut config = match Config::read(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+    config.merge_cli(&args);
+
+    let registry = AnimationRegistry::with_defaults();
+    if let Some(animation) = args.animation.as_deref().filter(|a| !registry.contains(a)) {
+        eprintln!(
+            "Unknown animation '{animation}'. Valid animations are: {}",
+            registry.names().join(", ")
+        );
+        std::process::exit(1);
+    }
+
+    let wordlist = Wordlist::from_config(&config.general).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+
+    let palette = Palette::from_config(&config.palette).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    });
+
+    let exporter = args.export_frames.as_deref().map(|directory| {
+        FrameExporter::new(directory, args.frames).unwrap_or_else(|err| {
+            eprintln!("Couldn't create export directory {directory:?}: {err}");
+            std::process::exit(1);
+        })
+    });
+
+    Window::from_config(
+        window_conf(&args),
+        run(args, config, registry, wordlist, palette, exporter),
+    );
+}
+
+async fn run(
+    args: CliArguments,
+    config: Config,
+    registry: AnimationRegistry,
+    wordlist: Wordlist,
+    palette: Palette,
+    mut exporter: Option<FrameExporter>,
+) {
     // Set seed for randomness.
-    let current_millisecond = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Unable to read systemt time.");
-    rand::srand(current_millisecond.as_secs());
-
-    setup();
-    let mut state = State::new().await;
-    state.grab_black_screen();
-
-    //let mut animation = Animation::new_wall();
-    //let mut animation = Animation::new_copter(
-    //    &state,
-    //    Vec2::new(state.window_width / 2.0, state.window_height / 2.0),
-    //);
-    let mut animation = Animation::new_word_chaos(&state);
+    // Fall back to the current time, if no seed has been specified.
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Unable to read systemt time.")
+            .as_secs()
+    });
+    // Print the seed, so the run can be reproduced with `--seed`.
+    println!("Seed: {seed}");
+    // External animations still use macroquad's global generator and the real frame time.
+    // Exports that contain them aren't reproducible.
+    rand::srand(seed);
+
+    // Exports use a fixed timestep, so they don't depend on the speed of the machine.
+    let clock = match exporter {
+        Some(_) => Clock::fixed(Duration::from_secs_f64(1.0 / args.fps as f64)),
+        None => Clock::realtime(),
+    };
+
+    let mut state = State::new(
+        config,
+        registry,
+        wordlist,
+        palette,
+        clock,
+        RngStreams::new(seed),
+    );
+    state.input_enabled = exporter.is_none();
+    // Exports only depend on the files as they were at startup.
+    if exporter.is_none() {
+        state.watch_files();
+    }
+
+    let mut animation = state.start_first_animation(args.animation.as_deref());
 
     loop {
-        clear_background(BLACK);
+        state.begin_frame();
 
-        // We're cycling through animations, only one can run at a time.
-        match animation {
-            Animation::Wall(ref mut inner) => {
-                inner.update(&state);
-                inner.draw(&state);
-            }
-            Animation::Copter(ref mut inner) => {
-                inner.update(&state);
-                inner.draw(&state);
-            }
-            Animation::WordChaos(ref mut inner) => {
-                inner.update(&state);
-                inner.draw(&state);
-            }
-            Animation::Snake(ref mut inner) => {
-                inner.update();
-                inner.draw();
+        // Only one animation can run at a time.
+        animation.update(&state);
+        animation.draw(&state);
+
+        state.draw(animation.as_ref());
+
+        if let Some(ref mut exporter) = exporter {
+            exporter.capture();
+            if exporter.is_done() {
+                break;
             }
         }
 
-        state.draw();
-
-        if let Some(next_animation) = state.update(&mut animation) {
+        if let Some(next_animation) = state.update(animation.as_mut()) {
             animation = next_animation;
         }
         next_frame().await
     }
 }
 
-fn setup() {
+fn setup(verbosity: u8) {
     // Beautify panics for better debug output.
     better_panic::install();
 
-    let verbosity = 0;
     // Set the verbosity level of the logger.
+    // Warnings are always shown, as they point out mistakes in the config, e.g. misspelled keys.
     let level = match verbosity {
-        0 => LevelFilter::Error,
-        1 => LevelFilter::Warn,
-        2 => LevelFilter::Info,
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
         _ => LevelFilter::Debug,
     };
-    SimpleLogger::init(level, Config::default()).unwrap();
+    SimpleLogger::init(level, LogConfig::default()).unwrap();
 }
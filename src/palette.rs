@@ -0,0 +1,387 @@
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use macroquad::color::Color;
+use serde::Deserialize;
+
+use crate::helper::cycle_colors;
+
+/// The names of all built-in themes.
+pub const THEMES: [&str; 5] = ["rainbow", "pastel", "neon", "monochrome", "high-contrast"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum PaletteError {
+    #[error("Unknown palette '{name}'. Use a palette file or one of: {}", THEMES.join(", "))]
+    UnknownTheme { name: String },
+    #[error("Couldn't read palette {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The location is the path and, if known, the line of the error.
+    #[error("{location}: {message}")]
+    Parse { location: String, message: String },
+}
+
+/// The `[palette]` section of the config file.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PaletteConfig {
+    /// Either the name of a built-in theme or the path to a palette file.
+    /// Palette files are either GIMP palettes (`.gpl`) or lists of hex colors.
+    pub theme: String,
+    /// Whether words get a smooth gradient through the whole palette.
+    /// Otherwise the colors of the palette are simply repeated.
+    pub gradient: bool,
+}
+
+impl Default for PaletteConfig {
+    fn default() -> Self {
+        PaletteConfig {
+            theme: "rainbow".to_string(),
+            gradient: false,
+        }
+    }
+}
+
+/// The colors, that are used by all animations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    pub name: String,
+    /// Palettes always contain at least a single color.
+    pub colors: Vec<Color>,
+    pub gradient: bool,
+}
+
+impl Palette {
+    /// Load the configured theme.
+    pub fn from_config(config: &PaletteConfig) -> Result<Palette, PaletteError> {
+        let (name, colors) = match theme(&config.theme) {
+            Some(colors) => (config.theme.clone(), colors),
+            None => {
+                let path = Path::new(&config.theme);
+                if !path.is_file() {
+                    return Err(PaletteError::UnknownTheme {
+                        name: config.theme.clone(),
+                    });
+                }
+                read(path)?
+            }
+        };
+
+        Ok(Palette {
+            name,
+            colors,
+            gradient: config.gradient,
+        })
+    }
+
+    /// The path of the palette file, if the palette isn't a built-in theme.
+    pub fn file(config: &PaletteConfig) -> Option<&Path> {
+        match theme(&config.theme) {
+            Some(_) => None,
+            None => Some(Path::new(&config.theme)),
+        }
+    }
+
+    /// A color for each grapheme of a word with the given length.
+    pub fn colors_for(&self, length: usize) -> Vec<Color> {
+        if self.gradient {
+            gradient(&self.colors, length)
+        } else {
+            cycle_colors(&self.colors, length)
+        }
+    }
+}
+
+/// Interpolate `length` colors, that evenly go from the first to the last color of the palette.
+pub fn gradient(palette: &[Color], length: usize) -> Vec<Color> {
+    match palette {
+        [] => Vec::new(),
+        [color] => vec![*color; length],
+        _ if length == 1 => vec![palette[0]],
+        _ => (0..length)
+            .map(|index| {
+                // The position of this color between two palette colors.
+                let position = index as f32 / (length - 1) as f32 * (palette.len() - 1) as f32;
+                let start = (position.floor() as usize).min(palette.len() - 2);
+                lerp(palette[start], palette[start + 1], position - start as f32)
+            })
+            .collect(),
+    }
+}
+
+fn lerp(from: Color, to: Color, amount: f32) -> Color {
+    Color::new(
+        from.r + (to.r - from.r) * amount,
+        from.g + (to.g - from.g) * amount,
+        from.b + (to.b - from.b) * amount,
+        from.a + (to.a - from.a) * amount,
+    )
+}
+
+/// The colors of a built-in theme.
+fn theme(name: &str) -> Option<Vec<Color>> {
+    let colors: &[(u8, u8, u8)] = match name {
+        "rainbow" => &[
+            // Red
+            (255, 0, 0),
+            // Orange
+            (255, 120, 0),
+            // Yellow
+            (255, 238, 0),
+            // Bright green
+            (153, 255, 0),
+            // Green
+            (38, 255, 0),
+            // Turquoise
+            (0, 255, 200),
+            // Bright blue
+            (0, 195, 255),
+            // Blue
+            (0, 76, 255),
+            // Purple
+            (157, 0, 255),
+            // Pink
+            (255, 0, 232),
+        ],
+        "pastel" => &[
+            (255, 179, 186),
+            (255, 223, 186),
+            (255, 255, 186),
+            (186, 255, 201),
+            (186, 225, 255),
+            (205, 186, 255),
+        ],
+        "neon" => &[
+            (255, 0, 110),
+            (251, 86, 7),
+            (255, 190, 11),
+            (57, 255, 20),
+            (0, 245, 255),
+            (131, 56, 236),
+        ],
+        "monochrome" => &[(255, 255, 255), (200, 200, 200), (150, 150, 150)],
+        "high-contrast" => &[(255, 255, 255), (255, 255, 0), (0, 255, 255), (255, 0, 255)],
+        _ => return None,
+    };
+
+    Some(
+        colors
+            .iter()
+            .map(|(r, g, b)| Color::from_rgba(*r, *g, *b, 255))
+            .collect(),
+    )
+}
+
+/// Read a palette file. The format is determined by the file extension.
+fn read(path: &Path) -> Result<(String, Vec<Color>), PaletteError> {
+    let content = read_to_string(path).map_err(|source| PaletteError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let result = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("gpl") => parse_gpl(&content),
+        _ => parse_hex_list(&content).map(|colors| (None, colors)),
+    };
+    let (name, colors) = result.map_err(|(line, message)| PaletteError::Parse {
+        location: match line {
+            Some(line) => format!("{}:{line}", path.display()),
+            None => path.display().to_string(),
+        },
+        message,
+    })?;
+
+    let name = name.unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+
+    Ok((name, colors))
+}
+
+/// A parse error with the (1-based) line, if it's known.
+type ParseError = (Option<usize>, String);
+
+/// Parse a GIMP palette, which looks like this:
+///
+/// ```text
+/// GIMP Palette
+/// Name: Sunset
+/// # Comments start with a hash.
+/// 255 94  77 Coral
+/// 255 195 0
+/// ```
+fn parse_gpl(content: &str) -> Result<(Option<String>, Vec<Color>), ParseError> {
+    let mut lines = content.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == "GIMP Palette" => (),
+        _ => return Err((Some(1), "Expected a 'GIMP Palette' header".to_string())),
+    }
+
+    let mut name = None;
+    let mut colors = Vec::new();
+    for (index, line) in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Columns:") {
+            continue;
+        }
+        if let Some(palette_name) = line.strip_prefix("Name:") {
+            name = Some(palette_name.trim().to_string());
+            continue;
+        }
+
+        let channels: Vec<u8> = line
+            .split_whitespace()
+            .take(3)
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()
+            .filter(|channels: &Vec<u8>| channels.len() == 3)
+            .ok_or_else(|| {
+                (
+                    Some(index + 1),
+                    format!("Expected three numbers from 0 to 255, got '{line}'"),
+                )
+            })?;
+        colors.push(Color::from_rgba(channels[0], channels[1], channels[2], 255));
+    }
+
+    if colors.is_empty() {
+        return Err((None, "The palette doesn't contain any colors".to_string()));
+    }
+
+    Ok((name, colors))
+}
+
+/// Parse a list of hex colors with one color per line.
+/// Empty lines and comments starting with `;` or `//` are ignored.
+fn parse_hex_list(content: &str) -> Result<Vec<Color>, ParseError> {
+    let mut colors = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with("//") {
+            continue;
+        }
+
+        let color = parse_hex(line)
+            .ok_or_else(|| (Some(index + 1), format!("Invalid hex color '{line}'")))?;
+        colors.push(color);
+    }
+
+    if colors.is_empty() {
+        return Err((None, "The palette doesn't contain any colors".to_string()));
+    }
+
+    Ok(colors)
+}
+
+/// Parse a color in the `#rrggbb` or `#rrggbbaa` notation. The leading `#` is optional.
+pub fn parse_hex(hex: &str) -> Option<Color> {
+    let hex = hex.trim().trim_start_matches('#');
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color::from_rgba(r, g, b, 255)
+    }
+
+    #[test]
+    fn all_themes_exist() {
+        for name in THEMES {
+            let config = PaletteConfig {
+                theme: name.to_string(),
+                ..Default::default()
+            };
+            let palette = Palette::from_config(&config).unwrap();
+            assert!(!palette.colors.is_empty());
+            assert!(Palette::file(&config).is_none());
+        }
+    }
+
+    #[test]
+    fn unknown_themes_are_rejected() {
+        let config = PaletteConfig {
+            theme: "does-not-exist".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Palette::from_config(&config),
+            Err(PaletteError::UnknownTheme { .. })
+        ));
+    }
+
+    #[test]
+    fn gradient_interpolates_between_colors() {
+        let palette = [rgb(0, 0, 0), rgb(255, 255, 255)];
+
+        let colors = gradient(&palette, 3);
+        assert_eq!(colors[0], palette[0]);
+        assert!((colors[1].r - 0.5).abs() < 0.01);
+        assert_eq!(colors[2], palette[1]);
+
+        assert_eq!(gradient(&palette, 1), vec![palette[0]]);
+        assert_eq!(gradient(&palette[..1], 4), vec![palette[0]; 4]);
+        assert!(gradient(&palette, 0).is_empty());
+    }
+
+    #[test]
+    fn gradient_hits_every_palette_color() {
+        let palette = [rgb(255, 0, 0), rgb(0, 255, 0), rgb(0, 0, 255)];
+
+        let colors = gradient(&palette, 5);
+        assert_eq!(colors.len(), 5);
+        assert_eq!(colors[0], palette[0]);
+        assert_eq!(colors[2], palette[1]);
+        assert_eq!(colors[4], palette[2]);
+    }
+
+    #[test]
+    fn gpl_files() {
+        let content =
+            "GIMP Palette\nName: Sunset\nColumns: 2\n# Comment\n255  94  77\tCoral\n  0 195   0\n";
+        let (name, colors) = parse_gpl(content).unwrap();
+
+        assert_eq!(name.as_deref(), Some("Sunset"));
+        assert_eq!(colors, vec![rgb(255, 94, 77), rgb(0, 195, 0)]);
+
+        let (line, _) = parse_gpl("GIMP Palette\n255 0\n").unwrap_err();
+        assert_eq!(line, Some(2));
+        assert!(parse_gpl("255 0 0\n").is_err());
+    }
+
+    #[test]
+    fn hex_lists() {
+        let content = "; Comment\n#ff0000\n\n00ff0080\n";
+        let colors = parse_hex_list(content).unwrap();
+
+        assert_eq!(
+            colors,
+            vec![rgb(255, 0, 0), Color::from_rgba(0, 255, 0, 128)]
+        );
+
+        let (line, _) = parse_hex_list("#ff0000\nred\n").unwrap_err();
+        assert_eq!(line, Some(2));
+        assert!(parse_hex_list("\n; Nothing\n").is_err());
+    }
+}
@@ -0,0 +1,466 @@
+use std::time::Duration;
+
+use log::warn;
+use macroquad::rand::{ChooseRandom, RandGenerator};
+use serde::Deserialize;
+
+use crate::transition::TransitionKind;
+
+/// How the next animation of the playlist is picked.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlaylistMode {
+    /// Play all entries in the order they're defined.
+    Ordered,
+    /// Play all entries in a random order.
+    /// Every entry is shown once, before any entry is repeated.
+    Shuffled,
+    /// Pick a random entry, entries with a higher weight are picked more often.
+    Weighted,
+}
+
+/// The `[playlist]` section of the config file.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PlaylistConfig {
+    pub mode: PlaylistMode,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+impl Default for PlaylistConfig {
+    fn default() -> Self {
+        // Word chaos has always been the first animation.
+        let entries = ["word-chaos", "snake", "copter", "wall"]
+            .into_iter()
+            .map(PlaylistEntry::new)
+            .collect();
+
+        PlaylistConfig {
+            mode: PlaylistMode::Ordered,
+            entries,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlaylistEntry {
+    /// The name of the animation as it's known to the registry.
+    pub animation: String,
+    /// How long this entry should be shown.
+    /// Falls back to the general `animation_duration`, if not set.
+    #[serde(default)]
+    pub duration: Option<EntryDuration>,
+    /// The relative weight of this entry in `weighted` mode.
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// The transition that's played, when this entry starts.
+    /// Falls back to the general `transition`, if not set.
+    #[serde(default)]
+    pub transition: Option<TransitionKind>,
+}
+
+impl PlaylistEntry {
+    pub fn new(animation: &str) -> PlaylistEntry {
+        PlaylistEntry {
+            animation: animation.to_string(),
+            duration: None,
+            weight: default_weight(),
+            transition: None,
+        }
+    }
+
+    /// Determine the duration of this entry.
+    /// A random duration is picked, if a range has been specified.
+    pub fn duration(&self, default: Duration, rng: &RandGenerator) -> Duration {
+        match self.duration {
+            None => default,
+            Some(EntryDuration::Fixed(seconds)) => Duration::from_secs_f64(seconds.max(0.0)),
+            Some(EntryDuration::Range([min, max])) => {
+                let (min, max) = (min.max(0.0), max.max(0.0));
+                if min >= max {
+                    return Duration::from_secs_f64(min);
+                }
+                Duration::from_secs_f64(rng.gen_range(min, max))
+            }
+        }
+    }
+}
+
+/// Either a fixed duration in seconds (`duration = 30`)
+/// or a range from which a random duration is picked (`duration = [10, 25]`).
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(untagged)]
+pub enum EntryDuration {
+    Fixed(f64),
+    Range([f64; 2]),
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// Decides which animation is shown next.
+///
+/// All picked entries are remembered, which allows to go back and forth in the history.
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    mode: PlaylistMode,
+    entries: Vec<PlaylistEntry>,
+    /// The indices of all entries that have been picked so far.
+    history: Vec<usize>,
+    /// The position of the current entry in the history.
+    cursor: usize,
+    /// The remaining entries of the current round in `shuffled` mode.
+    bag: Vec<usize>,
+    /// Problems with the configured entries, e.g. unknown animations.
+    warnings: Vec<String>,
+}
+
+impl Playlist {
+    /// Create a new playlist from the config.
+    /// Entries for animations that aren't known are skipped and a warning is logged.
+    pub fn new(config: &PlaylistConfig, known_animations: &[&str]) -> Playlist {
+        let mut warnings = Vec::new();
+        let mut entries: Vec<PlaylistEntry> = config
+            .entries
+            .iter()
+            .filter(|entry| {
+                let known = known_animations.contains(&entry.animation.as_str());
+                if !known {
+                    warnings.push(format!(
+                        "Skipping unknown animation '{}' in playlist",
+                        entry.animation
+                    ));
+                }
+                known
+            })
+            .cloned()
+            .collect();
+
+        // Fall back to all known animations, so there's always something to show.
+        if entries.is_empty() {
+            warnings.push("Playlist is empty, falling back to all animations.".to_string());
+            entries = known_animations
+                .iter()
+                .map(|name| PlaylistEntry::new(name))
+                .collect();
+        }
+
+        for warning in warnings.iter() {
+            warn!("{warning}");
+        }
+
+        Playlist {
+            mode: config.mode,
+            entries,
+            history: Vec::new(),
+            cursor: 0,
+            bag: Vec::new(),
+            warnings,
+        }
+    }
+
+    pub fn mode(&self) -> PlaylistMode {
+        self.mode
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// The entry that's currently playing.
+    pub fn current(&self) -> Option<&PlaylistEntry> {
+        self.history
+            .get(self.cursor)
+            .map(|index| &self.entries[*index])
+    }
+
+    /// Move to the next entry.
+    ///
+    /// If we previously went back in the history, we move forward through the history again,
+    /// before new entries are picked.
+    pub fn next_entry(&mut self, rng: &RandGenerator) -> &PlaylistEntry {
+        if self.cursor + 1 < self.history.len() {
+            self.cursor += 1;
+        } else {
+            let index = self.pick_next(rng);
+            self.history.push(index);
+            self.cursor = self.history.len() - 1;
+        }
+
+        &self.entries[self.history[self.cursor]]
+    }
+
+    /// Move to the previous entry in the history.
+    /// Stays at the first entry, if there's no history.
+    pub fn previous_entry(&mut self, rng: &RandGenerator) -> &PlaylistEntry {
+        if self.history.is_empty() {
+            return self.next_entry(rng);
+        }
+
+        self.cursor = self.cursor.saturating_sub(1);
+        &self.entries[self.history[self.cursor]]
+    }
+
+    /// Start the given animation, without respecting the playlist.
+    /// The playlist will continue after this entry.
+    pub fn jump_to(&mut self, animation: &str) -> PlaylistEntry {
+        match self
+            .entries
+            .iter()
+            .position(|entry| entry.animation == animation)
+        {
+            Some(index) => {
+                self.history.truncate(self.cursor + 1);
+                self.history.push(index);
+                self.cursor = self.history.len() - 1;
+                self.bag.retain(|bagged| *bagged != index);
+                self.entries[index].clone()
+            }
+            // Animations that aren't part of the playlist don't show up in the history.
+            None => PlaylistEntry::new(animation),
+        }
+    }
+
+    /// Replace the entries and mode of the playlist.
+    ///
+    /// The history is cleared, as the indices are no longer valid.
+    /// If the current animation is still part of the playlist, we continue from there.
+    pub fn reload(&mut self, config: &PlaylistConfig, known_animations: &[&str]) {
+        let current = self.current().map(|entry| entry.animation.clone());
+        *self = Playlist::new(config, known_animations);
+
+        if let Some(index) = current.and_then(|current| {
+            self.entries
+                .iter()
+                .position(|entry| entry.animation == current)
+        }) {
+            self.history.push(index);
+        }
+    }
+
+    fn pick_next(&mut self, rng: &RandGenerator) -> usize {
+        let last = self.history.last().copied();
+        match self.mode {
+            PlaylistMode::Ordered => match last {
+                Some(last) => (last + 1) % self.entries.len(),
+                None => 0,
+            },
+            PlaylistMode::Shuffled => {
+                if self.bag.is_empty() {
+                    self.bag = (0..self.entries.len()).collect();
+                    self.bag.shuffle_with_state(rng);
+                    // Don't show the same entry twice in a row, when starting a new round.
+                    if self.bag.len() > 1 && self.bag.last().copied() == last {
+                        self.bag.swap(0, self.entries.len() - 1);
+                    }
+                }
+                self.bag.pop().expect("Bag was just filled")
+            }
+            PlaylistMode::Weighted => {
+                let total: f32 = self.entries.iter().map(|entry| entry.weight.max(0.0)).sum();
+                if total <= 0.0 {
+                    return rng.gen_range(0, self.entries.len());
+                }
+
+                let mut pick = rng.gen_range(0.0, total);
+                for (index, entry) in self.entries.iter().enumerate() {
+                    pick -= entry.weight.max(0.0);
+                    if pick < 0.0 {
+                        return index;
+                    }
+                }
+                self.entries.len() - 1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANIMATIONS: [&str; 4] = ["word-chaos", "snake", "copter", "wall"];
+
+    fn playlist_of(mode: PlaylistMode, entries: Vec<PlaylistEntry>) -> Playlist {
+        Playlist::new(&PlaylistConfig { mode, entries }, &ANIMATIONS)
+    }
+
+    fn all_entries() -> Vec<PlaylistEntry> {
+        ANIMATIONS.into_iter().map(PlaylistEntry::new).collect()
+    }
+
+    fn next(playlist: &mut Playlist, rng: &RandGenerator) -> String {
+        playlist.next_entry(rng).animation.clone()
+    }
+
+    #[test]
+    fn default_playlist_starts_with_word_chaos() {
+        let rng = RandGenerator::new();
+        let mut playlist = Playlist::new(&PlaylistConfig::default(), &ANIMATIONS);
+
+        assert_eq!(next(&mut playlist, &rng), "word-chaos");
+    }
+
+    #[test]
+    fn ordered_playlists_wrap_around() {
+        let rng = RandGenerator::new();
+        let mut playlist = playlist_of(PlaylistMode::Ordered, all_entries());
+
+        let played: Vec<String> = (0..5).map(|_| next(&mut playlist, &rng)).collect();
+        assert_eq!(
+            played,
+            ["word-chaos", "snake", "copter", "wall", "word-chaos"]
+        );
+    }
+
+    #[test]
+    fn unknown_entries_are_skipped() {
+        let rng = RandGenerator::new();
+        let entries = vec![PlaylistEntry::new("unknown"), PlaylistEntry::new("wall")];
+        let mut playlist = playlist_of(PlaylistMode::Ordered, entries);
+        assert_eq!(
+            playlist.warnings(),
+            ["Skipping unknown animation 'unknown' in playlist"]
+        );
+        assert_eq!(next(&mut playlist, &rng), "wall");
+        assert_eq!(next(&mut playlist, &rng), "wall");
+
+        // Without any known entry, all animations are played.
+        let mut playlist = playlist_of(PlaylistMode::Ordered, vec![PlaylistEntry::new("unknown")]);
+        assert_eq!(playlist.warnings().len(), 2);
+        assert_eq!(next(&mut playlist, &rng), "word-chaos");
+        assert!(playlist_of(PlaylistMode::Ordered, all_entries())
+            .warnings()
+            .is_empty());
+    }
+
+    #[test]
+    fn shuffled_playlists_play_every_entry_once_per_round() {
+        let rng = RandGenerator::new();
+        let mut playlist = playlist_of(PlaylistMode::Shuffled, all_entries());
+
+        let mut previous: Option<String> = None;
+        for _ in 0..50 {
+            let mut round: Vec<String> = (0..4).map(|_| next(&mut playlist, &rng)).collect();
+            // No entry is repeated between two rounds.
+            assert_ne!(previous.as_ref(), Some(&round[0]));
+            previous = round.last().cloned();
+
+            round.sort();
+            assert_eq!(round, ["copter", "snake", "wall", "word-chaos"]);
+        }
+    }
+
+    #[test]
+    fn weighted_playlists_prefer_heavy_entries() {
+        let rng = RandGenerator::new();
+        let weighted = |animation: &str, weight: f32| PlaylistEntry {
+            weight,
+            ..PlaylistEntry::new(animation)
+        };
+        let entries = vec![
+            weighted("wall", 1.0),
+            weighted("copter", 9.0),
+            weighted("snake", 0.0),
+        ];
+        let mut playlist = playlist_of(PlaylistMode::Weighted, entries);
+
+        let played: Vec<String> = (0..1000).map(|_| next(&mut playlist, &rng)).collect();
+        let count = |animation: &str| played.iter().filter(|played| *played == animation).count();
+        assert_eq!(count("snake"), 0);
+        assert!(count("copter") > 800);
+        assert!(count("wall") > 50);
+
+        // Without any weight, all entries are equally likely.
+        let entries = vec![weighted("wall", 0.0), weighted("copter", 0.0)];
+        let mut playlist = playlist_of(PlaylistMode::Weighted, entries);
+        let played: Vec<String> = (0..100).map(|_| next(&mut playlist, &rng)).collect();
+        assert!(played.iter().any(|played| played == "wall"));
+        assert!(played.iter().any(|played| played == "copter"));
+    }
+
+    #[test]
+    fn durations_are_picked_from_ranges() {
+        let rng = RandGenerator::new();
+        let default = Duration::from_secs(80);
+        let with_duration = |duration| PlaylistEntry {
+            duration,
+            ..PlaylistEntry::new("wall")
+        };
+
+        assert_eq!(with_duration(None).duration(default, &rng), default);
+        let fixed = with_duration(Some(EntryDuration::Fixed(30.0)));
+        assert_eq!(fixed.duration(default, &rng), Duration::from_secs(30));
+
+        let range = with_duration(Some(EntryDuration::Range([10.0, 25.0])));
+        for _ in 0..100 {
+            let duration = range.duration(default, &rng);
+            assert!(duration >= Duration::from_secs(10) && duration <= Duration::from_secs(25));
+        }
+
+        // Reversed and negative ranges don't panic.
+        let reversed = with_duration(Some(EntryDuration::Range([25.0, 10.0])));
+        assert_eq!(reversed.duration(default, &rng), Duration::from_secs(25));
+        let negative = with_duration(Some(EntryDuration::Range([-5.0, -1.0])));
+        assert_eq!(negative.duration(default, &rng), Duration::ZERO);
+    }
+
+    #[test]
+    fn history_can_be_walked_back_and_forth() {
+        let rng = RandGenerator::new();
+        let mut playlist = playlist_of(PlaylistMode::Ordered, all_entries());
+        // Going back without a history starts the playlist.
+        assert_eq!(playlist.previous_entry(&rng).animation, "word-chaos");
+        next(&mut playlist, &rng);
+        next(&mut playlist, &rng);
+
+        assert_eq!(playlist.previous_entry(&rng).animation, "snake");
+        assert_eq!(playlist.previous_entry(&rng).animation, "word-chaos");
+        // The first entry is as far back as it goes.
+        assert_eq!(playlist.previous_entry(&rng).animation, "word-chaos");
+
+        // Moving forward replays the history, before new entries are picked.
+        assert_eq!(next(&mut playlist, &rng), "snake");
+        assert_eq!(next(&mut playlist, &rng), "copter");
+        assert_eq!(next(&mut playlist, &rng), "wall");
+    }
+
+    #[test]
+    fn jumps_replace_the_forward_history() {
+        let rng = RandGenerator::new();
+        let mut playlist = playlist_of(PlaylistMode::Ordered, all_entries());
+        next(&mut playlist, &rng);
+        next(&mut playlist, &rng);
+        next(&mut playlist, &rng);
+        playlist.previous_entry(&rng);
+
+        assert_eq!(playlist.jump_to("wall").animation, "wall");
+        assert_eq!(playlist.current().unwrap().animation, "wall");
+        // The playlist continues after the entry, that was jumped to.
+        assert_eq!(next(&mut playlist, &rng), "word-chaos");
+        assert_eq!(playlist.previous_entry(&rng).animation, "wall");
+        assert_eq!(playlist.previous_entry(&rng).animation, "snake");
+
+        // Unknown animations are played, but don't end up in the history.
+        assert_eq!(playlist.jump_to("unknown").animation, "unknown");
+        assert_eq!(playlist.current().unwrap().animation, "snake");
+    }
+
+    #[test]
+    fn jumped_to_entries_are_taken_out_of_the_bag() {
+        let rng = RandGenerator::new();
+        let mut playlist = playlist_of(PlaylistMode::Shuffled, all_entries());
+        let first = next(&mut playlist, &rng);
+        let jumped = ANIMATIONS
+            .into_iter()
+            .find(|animation| *animation != first)
+            .unwrap();
+        playlist.jump_to(jumped);
+
+        let mut rest: Vec<String> = (0..2).map(|_| next(&mut playlist, &rng)).collect();
+        rest.extend([first, jumped.to_string()]);
+        rest.sort();
+        assert_eq!(rest, ["copter", "snake", "wall", "word-chaos"]);
+    }
+}
@@ -0,0 +1,125 @@
+use macroquad::rand::RandGenerator;
+
+/// All random number generators of a single run.
+///
+/// Everything is derived from a single seed, so passing the same seed via `--seed`
+/// reproduces a run. The state and the active animation draw from separate streams.
+/// That way, the playlist and transitions don't depend on how many random numbers
+/// an animation used and vice versa.
+pub struct RngStreams {
+    seed: u64,
+    /// Used for decisions of the state, such as the playlist and transitions.
+    state: RandGenerator,
+    /// Used by the currently active animation. It's reseeded, whenever an animation starts.
+    animation: RandGenerator,
+    /// The amount of animations that have been started so far.
+    started_animations: u64,
+}
+
+impl RngStreams {
+    pub fn new(seed: u64) -> RngStreams {
+        let state = RandGenerator::new();
+        state.srand(mix(seed, fnv1a("state")));
+
+        RngStreams {
+            seed,
+            state,
+            animation: RandGenerator::new(),
+            started_animations: 0,
+        }
+    }
+
+    /// The seed this run has been started with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The stream of the state.
+    pub fn state(&self) -> &RandGenerator {
+        &self.state
+    }
+
+    /// The stream of the currently active animation.
+    pub fn animation(&self) -> &RandGenerator {
+        &self.animation
+    }
+
+    /// Start a fresh stream for the animation with the given name.
+    ///
+    /// The stream only depends on the seed, the name and how many animations ran before.
+    /// Showing the same animation twice thereby doesn't result in the exact same run.
+    pub fn start_animation(&mut self, name: &str) {
+        let seed = mix(mix(self.seed, fnv1a(name)), self.started_animations);
+        self.animation.srand(seed);
+        self.started_animations += 1;
+    }
+}
+
+/// A stable hash of a name.
+/// We can't use the std hasher, as its output isn't guaranteed to be the same across releases.
+fn fnv1a(name: &str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Combine two values into a new seed (splitmix64 finalizer).
+fn mix(seed: u64, value: u64) -> u64 {
+    let mut z = seed ^ value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(rng: &RandGenerator) -> Vec<u32> {
+        (0..10).map(|_| rng.gen_range(0, 1_000_000)).collect()
+    }
+
+    #[test]
+    fn same_seeds_reproduce_runs() {
+        let (mut first, mut second) = (RngStreams::new(42), RngStreams::new(42));
+        first.start_animation("copter");
+        second.start_animation("copter");
+
+        assert_eq!(draw(first.state()), draw(second.state()));
+        assert_eq!(draw(first.animation()), draw(second.animation()));
+        assert_ne!(draw(RngStreams::new(43).state()), draw(first.state()));
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let (mut busy, mut idle) = (RngStreams::new(42), RngStreams::new(42));
+        busy.start_animation("copter");
+        idle.start_animation("copter");
+
+        // The animation's random numbers don't change the decisions of the state.
+        for _ in 0..100 {
+            draw(busy.animation());
+        }
+        assert_eq!(draw(busy.state()), draw(idle.state()));
+
+        // Neither do the random numbers of the state change the next animation.
+        busy.start_animation("wall");
+        idle.start_animation("wall");
+        assert_eq!(draw(busy.animation()), draw(idle.animation()));
+    }
+
+    #[test]
+    fn each_animation_gets_its_own_stream() {
+        let mut streams = RngStreams::new(42);
+        streams.start_animation("copter");
+        let copter = draw(streams.animation());
+        streams.start_animation("copter");
+        let second_copter = draw(streams.animation());
+
+        let mut other = RngStreams::new(42);
+        other.start_animation("wall");
+        assert_ne!(copter, draw(other.animation()));
+        // The same animation doesn't play out the same way twice.
+        assert_ne!(copter, second_copter);
+    }
+}
@@ -1,57 +1,165 @@
-use macroquad::prelude::*;
-
-pub fn load_shaders() -> Vec<Material> {
-    let lens_material = load_material(
-        LENS_VERTEX_SHADER,
-        LENS_FRAGMENT_SHADER,
-        MaterialParams {
-            uniforms: vec![("Center".to_owned(), UniformType::Float2)],
-            ..Default::default()
-        },
-    )
-    .unwrap();
-
-    vec![lens_material]
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use macroquad::{
+    miniquad::{ShaderError, ShaderType},
+    prelude::*,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ShaderLoadError {
+    #[error("Couldn't read shader {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Couldn't compile {name}:\n{message}")]
+    Compile { name: String, message: String },
 }
 
-const LENS_FRAGMENT_SHADER: &str = r#"#version 100
-precision lowp float;
+/// All materials that are used by animations and transitions.
+#[derive(Debug, Clone, Copy)]
+pub struct Shaders {
+    pub lens: Material,
+    /// Hides everything inside a growing circle.
+    pub iris: Material,
+    /// Hides random blocks of pixels.
+    pub dissolve: Material,
+}
 
-varying vec2 uv;
-varying vec2 uv_screen;
-varying vec2 center;
+/// The GLSL source of a single shader stage.
+/// A file of the same name in the shader directory takes precedence over the built-in source.
+struct ShaderSource {
+    name: &'static str,
+    builtin: &'static str,
+}
 
-uniform sampler2D _ScreenTexture;
+const LENS_VERTEX_SHADER: ShaderSource = ShaderSource {
+    name: "lens.vert",
+    builtin: include_str!("../shaders/lens.vert"),
+};
+const LENS_FRAGMENT_SHADER: ShaderSource = ShaderSource {
+    name: "lens.frag",
+    builtin: include_str!("../shaders/lens.frag"),
+};
+const TRANSITION_VERTEX_SHADER: ShaderSource = ShaderSource {
+    name: "transition.vert",
+    builtin: include_str!("../shaders/transition.vert"),
+};
+const IRIS_FRAGMENT_SHADER: ShaderSource = ShaderSource {
+    name: "iris.frag",
+    builtin: include_str!("../shaders/iris.frag"),
+};
+const DISSOLVE_FRAGMENT_SHADER: ShaderSource = ShaderSource {
+    name: "dissolve.frag",
+    builtin: include_str!("../shaders/dissolve.frag"),
+};
 
-void main() {
-    float gradient = length(uv);
-    vec2 uv_zoom = (uv_screen - center) * gradient + center;
+impl ShaderSource {
+    fn read(&self, directory: Option<&Path>) -> Result<String, ShaderLoadError> {
+        let Some(path) = directory
+            .map(|directory| directory.join(self.name))
+            .filter(|path| path.exists())
+        else {
+            return Ok(self.builtin.to_string());
+        };
 
-    gl_FragColor = texture2D(_ScreenTexture, uv_zoom);
+        read_to_string(&path).map_err(|source| ShaderLoadError::Io { path, source })
+    }
 }
-"#;
 
-const LENS_VERTEX_SHADER: &str = "#version 100
-attribute vec3 position;
-attribute vec2 texcoord;
+/// Compile all shaders.
+///
+/// Sources are read from the given directory, if it contains them.
+/// If a single shader fails to compile, none of the materials are kept.
+pub fn load_shaders(directory: Option<&Path>) -> Result<Shaders, ShaderLoadError> {
+    let transition_params = || MaterialParams {
+        uniforms: vec![
+            ("Progress".to_owned(), UniformType::Float1),
+            ("Resolution".to_owned(), UniformType::Float2),
+        ],
+        ..Default::default()
+    };
 
-varying lowp vec2 center;
-varying lowp vec2 uv;
-varying lowp vec2 uv_screen;
+    let programs = [
+        (
+            &LENS_VERTEX_SHADER,
+            &LENS_FRAGMENT_SHADER,
+            MaterialParams {
+                uniforms: vec![("Center".to_owned(), UniformType::Float2)],
+                ..Default::default()
+            },
+        ),
+        (
+            &TRANSITION_VERTEX_SHADER,
+            &IRIS_FRAGMENT_SHADER,
+            transition_params(),
+        ),
+        (
+            &TRANSITION_VERTEX_SHADER,
+            &DISSOLVE_FRAGMENT_SHADER,
+            transition_params(),
+        ),
+    ];
 
-uniform mat4 Model;
-uniform mat4 Projection;
+    let mut materials = Vec::new();
+    for (vertex, fragment, params) in programs {
+        match load(directory, vertex, fragment, params) {
+            Ok(material) => materials.push(material),
+            Err(err) => {
+                for material in materials.iter_mut() {
+                    material.delete();
+                }
+                return Err(err);
+            }
+        }
+    }
 
-uniform vec2 Center;
+    Ok(Shaders {
+        lens: materials[0],
+        iris: materials[1],
+        dissolve: materials[2],
+    })
+}
 
-void main() {
-    vec4 res = Projection * Model * vec4(position, 1);
-    vec4 c = Projection * Model * vec4(Center, 0, 1);
+impl Shaders {
+    /// Free all materials. They mustn't be used afterwards.
+    pub fn delete(&mut self) {
+        self.lens.delete();
+        self.iris.delete();
+        self.dissolve.delete();
+    }
+}
 
-    uv_screen = res.xy / 2.0 + vec2(0.5, 0.5);
-    center = c.xy / 2.0 + vec2(0.5, 0.5);
-    uv = texcoord;
+fn load(
+    directory: Option<&Path>,
+    vertex: &ShaderSource,
+    fragment: &ShaderSource,
+    params: MaterialParams,
+) -> Result<Material, ShaderLoadError> {
+    let vertex_source = vertex.read(directory)?;
+    let fragment_source = fragment.read(directory)?;
 
-    gl_Position = res;
+    load_material(&vertex_source, &fragment_source, params).map_err(|err| {
+        let (name, message) = match err {
+            ShaderError::CompilationError {
+                shader_type: ShaderType::Vertex,
+                error_message,
+            } => (vertex.name.to_string(), error_message),
+            ShaderError::CompilationError {
+                shader_type: ShaderType::Fragment,
+                error_message,
+            } => (fragment.name.to_string(), error_message),
+            ShaderError::LinkError(message) => {
+                (format!("{} and {}", vertex.name, fragment.name), message)
+            }
+            ShaderError::FFINulError(err) => (
+                format!("{} or {}", vertex.name, fragment.name),
+                err.to_string(),
+            ),
+        };
+        ShaderLoadError::Compile { name, message }
+    })
 }
-";
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use macroquad::{prelude::Vec2, rand::RandGenerator};
+
+use crate::{input::Input, state::State};
+
+/// Everything a single simulation step of an animation may depend on.
+///
+/// None of this requires a graphics context, which is why the game logic of the animations
+/// can run (and be tested) headless. The drawing layer of each animation lives on top of it.
+#[derive(Clone, Copy)]
+pub struct Step<'a> {
+    pub input: &'a Input,
+    /// The time since the last step.
+    pub delta: Duration,
+    /// The total time since the run has been started.
+    pub elapsed: Duration,
+    pub window_width: f32,
+    pub window_height: f32,
+    /// The size of a single character cell of the current font.
+    pub glyph: Vec2,
+    pub rng: &'a RandGenerator,
+}
+
+impl<'a> Step<'a> {
+    /// Take everything that's needed for the current step from the state.
+    pub fn from_state(state: &'a State) -> Step<'a> {
+        Step {
+            input: &state.input,
+            delta: state.clock.delta(),
+            elapsed: state.clock.elapsed(),
+            window_width: state.window_width,
+            window_height: state.window_height,
+            glyph: Vec2::new(state.font_dimensions.width, state.font_dimensions.height),
+            rng: state.rng.animation(),
+        }
+    }
+
+    /// The time since the last step in seconds.
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+}
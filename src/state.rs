@@ -1,43 +1,66 @@
-use std::{fs::read_to_string, ops::Add, path::Path, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Add,
+    time::Duration,
+};
 
-use macroquad::{prelude::*, rand::ChooseRandom};
+use log::{error, info};
+use macroquad::prelude::*;
 
 use crate::{
-    animations::{Animation, RoflcopterState},
-    color,
-    helper::*,
-    shaders::load_shaders,
+    animations::{Animation, AnimationRegistry},
+    clock::Clock,
+    config::Config,
+    fonts::{FontLibrary, FontMode},
+    helper::{grapheme_count, render_target_camera, TextRasterizer},
+    input::Input,
+    palette::Palette,
+    playlist::{Playlist, PlaylistEntry},
+    rng::RngStreams,
+    shaders::{load_shaders, Shaders},
+    transition::{Transition, TransitionKind},
+    watcher::{FileWatcher, Reloadable},
+    wordlist::{WordEntry, Wordlist},
 };
 
-pub struct Transition {
-    pub timer: Duration,
-    pub phase: Phase,
-}
-
-#[derive(Debug)]
-pub enum Phase {
-    In,
-    Out,
+/// A request to leave the current animation.
+#[derive(Debug, Clone, Copy)]
+enum Skip {
+    Next,
+    Previous,
 }
 
-/// The percentile of the screen height, a single char should occupy.
-static RELATIVE_FONT_SIZE: f32 = 60.0;
-
 pub struct State {
+    pub config: Config,
+    /// All animations that can be shown.
+    pub registry: AnimationRegistry,
+    /// The name of the currently active animation.
+    pub current_animation: String,
+    /// Decides which animation should be shown next.
+    pub playlist: Playlist,
+
     pub word: String,
     pub font: Font,
+    /// The name of the font, e.g. `RobotoMono-Bold`.
+    pub font_name: String,
+    /// All fonts, that have been loaded so far.
+    fonts: FontLibrary,
     pub font_size: u16,
     pub font_dimensions: TextDimensions,
-    /// For each character of the word, a color will be assigned.
+    /// The colors, that are used by all animations.
+    pub palette: Palette,
+    /// For each grapheme of the word, a color of the palette will be assigned.
     pub colors: Vec<Color>,
+    /// Renders text into textures and caches the glyphs.
+    pub rasterizer: TextRasterizer,
 
-    /// A wordlist of random words that can be picked.
-    pub wordlist: Vec<String>,
+    /// The words that can be picked by the animations.
+    pub wordlist: Wordlist,
 
     /// Whether it's time to skip the current animation.
     pub show_debug: bool,
     /// Whether it's time to skip the current animation.
-    skip_animation: bool,
+    skip_animation: Option<Skip>,
     halt_animation_changes: bool,
 
     /// The total time of the duration and current animation length.
@@ -48,82 +71,168 @@ pub struct State {
     pub transition_duration: Duration,
     /// The current state of the transition, if one is active
     pub transition: Option<Transition>,
-    /// A black screen, which is used to simulate transitions between animations.
-    pub black_screen: Texture2D,
+    /// The current animation is drawn onto this canvas, before it's drawn to the screen.
+    pub canvas: RenderTarget,
+    /// The last frame of the previous animation, which is used during transitions.
+    pub previous_frame: RenderTarget,
 
     pub window_height: f32,
     pub window_width: f32,
-    pub mouse_position: (f32, f32),
-    pub shaders: Vec<Material>,
+    /// The source of time for all animations.
+    pub clock: Clock,
+    /// The source of randomness for all animations.
+    pub rng: RngStreams,
+    pub input: Input,
+    /// Whether the user may interact with the animations.
+    /// This is disabled for reproducible runs, such as the frame export.
+    pub input_enabled: bool,
+    pub shaders: Shaders,
+    /// Shaders that have been replaced. They're freed once the frame that used them is done.
+    retired_shaders: Vec<Shaders>,
+
+    /// Notices changes of the config, wordlist and shaders, if file watching is enabled.
+    watcher: Option<FileWatcher>,
+    /// Everything that should be reloaded during the next update.
+    pending_reloads: BTreeSet<Reloadable>,
+    /// The errors of failed reloads, which are shown in the debug overlay.
+    reload_errors: BTreeMap<Reloadable, String>,
 }
 
 impl State {
-    pub async fn new() -> Self {
+    pub fn new(
+        config: Config,
+        registry: AnimationRegistry,
+        wordlist: Wordlist,
+        palette: Palette,
+        clock: Clock,
+        rng: RngStreams,
+    ) -> Self {
         let window_height = screen_height();
         let window_width = screen_width();
 
-        let font = load_ttf_font("fonts/RobotoMono-SemiBold.ttf")
-            .await
-            .expect("Font couldn't be loaded");
-        let font_size = (window_height / RELATIVE_FONT_SIZE) as u16;
+        let mut fonts = FontLibrary::new();
+        let (font, font_name) = fonts.load(&config.general.font).unwrap_or_else(|err| {
+            error!("{err}. Falling back to the bundled font.");
+            fonts.default_font()
+        });
+        let font_size = (window_height / config.general.relative_font_size) as u16;
         let font_dimensions = measure_text("j", Some(font), font_size, 1.0);
 
-        let word = "ROFLCOPTER".to_string();
-        let mut colors = color::create_colors();
-        colors.truncate(word.len());
-
-        let mut wordlist = vec![word.clone()];
-        let wordlist_path = Path::new("./wordlist.txt");
-        if wordlist_path.exists() {
-            let content = read_to_string(wordlist_path).expect("Failed while reading wordlist.");
-            wordlist = content.split('\n').map(|word| word.to_owned()).collect();
-        }
+        let word = config.general.word.clone();
+        let colors = palette.colors_for(grapheme_count(&word));
 
         //let animation_duration = Duration::from_secs(gen_range(10, 25));
-        let animation_duration = Duration::from_secs(80);
+        let animation_duration = config.general.animation_duration;
         let animation_timer = Duration::from_secs(0);
 
-        // Start the phase in transition animation
-        let transition = Some(Transition {
-            timer: Duration::from_secs(0),
-            phase: Phase::In,
+        // Broken shader files shouldn't prevent the start, so we fall back to the built-in ones.
+        let mut reload_errors = BTreeMap::new();
+        let shaders = load_shaders(Some(&config.general.shaders)).unwrap_or_else(|err| {
+            error!("{err}");
+            reload_errors.insert(Reloadable::Shaders, err.to_string());
+            load_shaders(None).expect("The built-in shaders always compile")
         });
 
         State {
             word,
             font,
+            font_name,
+            fonts,
             font_size,
             font_dimensions,
+            palette,
             colors,
+            rasterizer: TextRasterizer::new(),
 
             wordlist,
 
             show_debug: false,
-            skip_animation: false,
+            skip_animation: None,
             halt_animation_changes: false,
 
             animation_duration,
             animation_timer,
 
-            transition_duration: Duration::from_secs(2),
-            transition,
-            black_screen: Texture2D::empty(),
+            transition_duration: config.general.transition_duration,
+            transition: None,
+            canvas: black_render_target(window_width, window_height),
+            previous_frame: black_render_target(window_width, window_height),
 
             window_height,
             window_width,
-            mouse_position: mouse_position(),
-            shaders: load_shaders(),
+            clock,
+            rng,
+            input: Input::default(),
+            input_enabled: true,
+            shaders,
+            retired_shaders: Vec::new(),
+            watcher: None,
+            pending_reloads: BTreeSet::new(),
+            reload_errors,
+            playlist: Playlist::new(&config.playlist, &registry.names()),
+            config,
+            registry,
+            current_animation: String::new(),
+        }
+    }
+
+    /// Start the first animation.
+    /// If no animation is given, the first entry of the playlist is used.
+    pub fn start_first_animation(&mut self, name: Option<&str>) -> Box<dyn Animation> {
+        let entry = match name {
+            Some(name) => self.playlist.jump_to(name),
+            None => self.playlist.next_entry(self.rng.state()).clone(),
+        };
+
+        // There's no previous animation yet, so we fade in from black.
+        self.start_entry(&entry, TransitionKind::Fade)
+    }
+
+    /// Create the animation of a playlist entry and make it the active animation.
+    /// The given transition leads from the previous frame to the new animation.
+    fn start_entry(
+        &mut self,
+        entry: &PlaylistEntry,
+        transition: TransitionKind,
+    ) -> Box<dyn Animation> {
+        self.animation_duration =
+            entry.duration(self.config.general.animation_duration, self.rng.state());
+        self.animation_timer = Duration::from_secs(0);
+
+        if let Some(index) = self
+            .config
+            .general
+            .font_mode
+            .pick(&self.font_name, self.rng.state())
+        {
+            let font = self.fonts.bundled(index);
+            self.set_font(font);
         }
+
+        // Every animation gets a fresh random number stream.
+        self.rng.start_animation(&entry.animation);
+
+        let mut animation = self
+            .registry
+            .create(&entry.animation, self)
+            .unwrap_or_else(|| panic!("Unknown animation {}", entry.animation));
+        self.current_animation = entry.animation.clone();
+        animation.on_enter(self);
+
+        self.transition = Some(Transition::new(self, transition));
+
+        animation
     }
 
-    pub fn update(&mut self, animation: &mut Animation) -> Option<Animation> {
+    pub fn update(&mut self, animation: &mut dyn Animation) -> Option<Box<dyn Animation>> {
         // Check if the window has been resized and update stuff accordingly.
         self.handle_window_resize(animation);
-        self.handle_mouse_update(animation);
-        self.handle_key_presses();
+        self.handle_input(animation);
+        self.handle_key_presses(animation);
+        self.handle_reloads(animation);
 
-        let mut next_animation: Option<Animation> = None;
-        let delta_time = delta_duration();
+        let mut next_animation: Option<Box<dyn Animation>> = None;
+        let delta_time = self.clock.delta();
 
         if !self.halt_animation_changes {
             // Tick the timer for the current animation.
@@ -137,123 +246,109 @@ impl State {
             } else {
                 transition.timer = transition.timer.add(delta_time);
             }
-        } else if self.animation_timer > self.animation_duration.add(self.transition_duration) {
-            // There's no transition, check if we should start one.
-            // This should be done, if the animation is finished.
-            self.transition = Some(Transition {
-                timer: Duration::from_secs(0),
-                phase: Phase::Out,
-            })
         }
 
-        // The current transition has finished and the transition animation is done.
-        if self.animation_timer > self.animation_duration.add(self.transition_duration * 2) {
-            self.skip_animation = true;
+        // The animation is finished, switch to the next one.
+        let finished = !self.halt_animation_changes && animation.is_finished();
+        if self.animation_timer > self.animation_duration || finished {
+            self.skip_animation = Some(Skip::Next);
         }
 
         // Switch to the next animation.
-        if self.skip_animation {
-            next_animation = Some(match animation {
-                Animation::Wall(_) => Animation::new_copter(self),
-                Animation::Copter(_) => Animation::new_word_chaos(self),
-                Animation::WordChaos(_) => Animation::new_snake(),
-                Animation::Snake(_) => Animation::new_copter(self),
-            });
-
-            self.skip_animation = false;
-            self.animation_timer = Duration::from_secs(0);
-
-            // Start the phase in transition animation
-            self.transition = Some(Transition {
-                timer: Duration::from_secs(0),
-                phase: Phase::In,
-            });
+        if let Some(skip) = self.skip_animation.take() {
+            let entry = match skip {
+                Skip::Next => self.playlist.next_entry(self.rng.state()).clone(),
+                Skip::Previous => self.playlist.previous_entry(self.rng.state()).clone(),
+            };
+            animation.on_exit(self);
+
+            // Keep the last frame of the current animation for the transition.
+            // The next animation simply draws onto the old frame's canvas.
+            std::mem::swap(&mut self.canvas, &mut self.previous_frame);
+
+            let transition = entry.transition.unwrap_or(self.config.general.transition);
+            next_animation = Some(self.start_entry(&entry, transition));
         }
 
         next_animation
     }
 
-    /// Animation independant draw logic.
-    /// This includes animation transition logic.
-    pub fn draw(&self) {
-        if let Some(ref transition) = self.transition {
-            // Calculate the gradiant, depending on the current state of the transition and,
-            // whether it's a phase in or a phase out.
-            let gradiant = (transition.timer.as_millis() as f64
-                / self.transition_duration.as_millis() as f64) as f32;
-            let gradiant = match transition.phase {
-                Phase::In => 1.0 - gradiant,
-                Phase::Out => gradiant,
-            };
+    /// Advance the clock and prepare the canvas for the next frame of the current animation.
+    /// All drawing calls up to [State::draw] end up on the canvas.
+    pub fn begin_frame(&mut self) {
+        self.clock.tick();
 
-            draw_texture_ex(
-                self.black_screen,
-                0.0,
-                0.0,
-                Color::new(0.0, 0.0, 0.0, gradiant),
-                DrawTextureParams {
-                    flip_y: true,
-                    ..Default::default()
-                },
-            )
+        // The previous frame has been rendered, so replaced shaders aren't in use anymore.
+        for mut shaders in self.retired_shaders.drain(..) {
+            shaders.delete();
         }
 
-        if self.show_debug {
-            draw_text(&format!("FPS: {}", get_fps()), 20.0, 20.0, 20.0, WHITE);
-            draw_text(
-                &format!("Duration: {:?}", self.animation_duration),
-                20.0,
-                40.0,
-                20.0,
-                WHITE,
-            );
-            draw_text(
-                &format!("Timer: {:.2?}", self.animation_timer),
-                20.0,
-                60.0,
-                20.0,
-                WHITE,
-            );
-        }
+        set_camera(&render_target_camera(
+            self.canvas,
+            self.window_width,
+            self.window_height,
+        ));
+        clear_background(BLACK);
     }
 
-    pub fn handle_mouse_update(&mut self, animation: &mut Animation) {
-        let (x, y) = mouse_position();
+    /// Animation independant draw logic.
+    /// This draws the canvas to the screen, including the transition and the debug overlay.
+    pub fn draw(&self, animation: &dyn Animation) {
+        set_default_camera();
+        draw_texture_ex(
+            self.canvas.texture,
+            0.0,
+            0.0,
+            WHITE,
+            DrawTextureParams {
+                flip_y: true,
+                ..Default::default()
+            },
+        );
 
-        if x == self.mouse_position.0 || y == self.mouse_position.1 {
-            return;
+        if let Some(ref transition) = self.transition {
+            transition.draw(self, self.previous_frame.texture);
         }
 
-        self.mouse_position = (x, y);
+        if self.show_debug {
+            let mut lines = vec![
+                format!("FPS: {}", get_fps()),
+                format!("Duration: {:?}", self.animation_duration),
+                format!("Timer: {:.2?}", self.animation_timer),
+                format!("Seed: {}", self.rng.seed()),
+                format!("Palette: {}", self.palette.name),
+                format!("Font: {}", self.font_name),
+                format!(
+                    "Animation: {} ({:?})",
+                    self.current_animation,
+                    self.playlist.mode()
+                ),
+            ];
+            lines.append(&mut animation.debug_info());
+            for (target, error) in self.reload_errors.iter() {
+                lines.push(format!("Failed to reload the {target}:"));
+                lines.extend(error.lines().map(|line| format!("    {line}")));
+            }
 
-        // While in copter mode, the copter should follow the mouse.
-        if let Animation::Copter(ref mut copter) = animation {
-            match copter.roflcopter_state {
-                RoflcopterState::Hovering { position, .. } => {
-                    copter.roflcopter_state = RoflcopterState::Flying {
-                        position,
-                        dest: Vec2::new(x, y),
-                    };
-                }
-                RoflcopterState::Flying {
-                    ref mut dest,
-                    ref position,
-                    ..
-                } => {
-                    let dimensions = copter.textures.copter_dimensions();
-                    let new_dest = match side(position, dest) {
-                        Side::Left => Vec2::new(x, y - dimensions.y / 2.0),
-                        Side::Right => Vec2::new(x - dimensions.x, y - dimensions.y / 2.0),
-                    };
-                    *dest = new_dest;
-                }
+            for (index, line) in lines.iter().enumerate() {
+                draw_text(line, 20.0, 20.0 * (index + 1) as f32, 20.0, WHITE);
             }
         }
     }
 
+    /// Take a new input snapshot and pass it to the current animation.
+    pub fn handle_input(&mut self, animation: &mut dyn Animation) {
+        if !self.input_enabled {
+            return;
+        }
+
+        self.input = Input::capture(&self.input);
+        animation.on_input(self, &self.input);
+    }
+
     /// Check whether the window size changed.
     /// If that's the case, update everything, that is depending on that size.
-    pub fn handle_window_resize(&mut self, animation: &mut Animation) {
+    pub fn handle_window_resize(&mut self, animation: &mut dyn Animation) {
         let height = screen_height();
         let width = screen_width();
 
@@ -262,39 +357,58 @@ impl State {
             self.window_height = height;
             self.window_width = width;
 
-            self.font_size = (self.window_height / RELATIVE_FONT_SIZE) as u16;
-            self.font_dimensions = measure_text("j", Some(self.font), self.font_size, 1.0);
+            self.update_font_size();
 
-            // Grab an updated transition screen
-            self.grab_black_screen();
+            // The render targets need to match the new screen size.
+            self.canvas.delete();
+            self.previous_frame.delete();
+            self.canvas = black_render_target(width, height);
+            self.previous_frame = black_render_target(width, height);
 
-            match animation {
-                Animation::Wall(_) => {}
-                Animation::Copter(inner) => inner.textures.update(self),
-                Animation::WordChaos(inner) => inner.update_texture(self),
-                Animation::Snake(_) => {}
-            }
+            animation.on_resize(self);
         }
     }
 
-    fn handle_key_presses(&mut self) {
-        if is_key_pressed(macroquad::prelude::KeyCode::D) {
+    fn handle_key_presses(&mut self, animation: &dyn Animation) {
+        if !self.input_enabled {
+            return;
+        }
+
+        // Keys that are used by the animation don't trigger any hotkeys.
+        let reserved = animation.reserved_keys();
+        let hotkey = |key: KeyCode| !reserved.contains(&key) && is_key_pressed(key);
+
+        if hotkey(KeyCode::D) {
             self.show_debug = !self.show_debug;
         }
-        if is_key_pressed(macroquad::prelude::KeyCode::S) {
-            self.skip_animation = true;
+        // Skip to the next animation with S or N and go back to the previous one with P.
+        if hotkey(KeyCode::S) || hotkey(KeyCode::N) {
+            self.skip_animation = Some(Skip::Next);
+        }
+        if hotkey(KeyCode::P) {
+            self.skip_animation = Some(Skip::Previous);
+        }
+
+        // Reload the config, wordlist, palette and shaders with R
+        if hotkey(KeyCode::R) {
+            self.pending_reloads.extend([
+                Reloadable::Config,
+                Reloadable::Wordlist,
+                Reloadable::Palette,
+                Reloadable::Shaders,
+            ]);
         }
 
         // Halt animations switches with H
-        if is_key_pressed(macroquad::prelude::KeyCode::H) {
+        if hotkey(KeyCode::H) {
             self.halt_animation_changes = !self.halt_animation_changes;
         }
 
         // Change the animation duration by one sec
-        if is_key_pressed(macroquad::prelude::KeyCode::K) {
+        if hotkey(KeyCode::K) {
             self.animation_duration += Duration::from_secs(1);
         }
-        if is_key_pressed(macroquad::prelude::KeyCode::J) {
+        if hotkey(KeyCode::J) {
             self.animation_duration -= Duration::from_secs(1);
             if self.animation_duration < Duration::from_secs(2) {
                 self.animation_duration = Duration::from_secs(2);
@@ -302,16 +416,180 @@ impl State {
         }
     }
 
-    pub fn grab_black_screen(&mut self) {
-        clear_background(BLACK);
-        let image = get_screen_data();
-        self.black_screen = Texture2D::from_image(&image);
+    /// Switch to another font. Animations have to regenerate their textures afterwards.
+    fn set_font(&mut self, (font, name): (Font, String)) {
+        self.font = font;
+        self.font_name = name;
+        self.update_font_size();
+    }
+
+    /// Recalculate everything, that depends on the font and its size.
+    fn update_font_size(&mut self) {
+        self.font_size = (self.window_height / self.config.general.relative_font_size) as u16;
+        self.font_dimensions = measure_text("j", Some(self.font), self.font_size, 1.0);
+        // Glyphs of the old font size won't be needed anymore.
+        self.rasterizer.clear();
+    }
+
+    /// Reload the config, wordlist, palette and shaders, whenever they change on disk.
+    pub fn watch_files(&mut self) {
+        match FileWatcher::new() {
+            Ok(watcher) => {
+                self.watcher = Some(watcher);
+                self.update_watched_paths();
+            }
+            Err(err) => error!("Couldn't start the file watcher: {err}"),
+        }
+    }
+
+    fn update_watched_paths(&mut self) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        let general = &self.config.general;
+        let paths = [
+            (Reloadable::Config, self.config.path.as_deref()),
+            (Reloadable::Wordlist, Some(general.wordlist.as_path())),
+            (Reloadable::Palette, Palette::file(&self.config.palette)),
+            (Reloadable::Shaders, Some(general.shaders.as_path())),
+        ];
+        for (target, path) in paths {
+            let Some(path) = path else {
+                continue;
+            };
+            if let Err(err) = watcher.watch(target, path) {
+                info!("Not watching {path:?} for changes: {err}");
+            }
+        }
+    }
+
+    /// Reload everything that changed on disk or has been requested.
+    /// If a reload fails, the last working version is kept.
+    fn handle_reloads(&mut self, animation: &mut dyn Animation) {
+        if let Some(watcher) = &self.watcher {
+            self.pending_reloads.extend(watcher.changes());
+        }
+
+        // The config is reloaded first, as it may point to another wordlist or shader directory.
+        while let Some(target) = self.pending_reloads.pop_first() {
+            let result = match target {
+                Reloadable::Config => self.reload_config(animation),
+                Reloadable::Wordlist => self.reload_wordlist(),
+                Reloadable::Palette => self.reload_palette(animation),
+                Reloadable::Shaders => self.reload_shaders(),
+            };
+
+            match result {
+                Ok(()) => {
+                    info!("Reloaded the {target}");
+                    self.reload_errors.remove(&target);
+                }
+                Err(err) => {
+                    error!("Failed to reload the {target}: {err}");
+                    self.reload_errors.insert(target, err);
+                }
+            }
+        }
     }
 
-    pub fn random_word(&self) -> String {
+    /// Read the config file again and apply all changed values.
+    /// Animation specific settings and the animation duration take effect with the next animation.
+    fn reload_config(&mut self, animation: &mut dyn Animation) -> Result<(), String> {
+        let config = self.config.reload().map_err(|err| err.to_string())?;
+
+        // Other modes change the font with the next animation.
+        let font_changed = config.general.font_mode == FontMode::Fixed
+            && (config.general.font != self.config.general.font
+                || config.general.font_mode != self.config.general.font_mode);
+        let font = if font_changed {
+            let font = self.fonts.load(&config.general.font);
+            Some(font.map_err(|err| err.to_string())?)
+        } else {
+            None
+        };
+
+        let old = std::mem::replace(&mut self.config, config);
+        let general = &self.config.general;
+
+        self.playlist
+            .reload(&self.config.playlist, &self.registry.names());
+        self.transition_duration = general.transition_duration;
+
+        if general.word != old.general.word {
+            self.word = general.word.clone();
+            self.colors = self.palette.colors_for(grapheme_count(&self.word));
+        }
+        if general.wordlist != old.general.wordlist {
+            self.pending_reloads.insert(Reloadable::Wordlist);
+        }
+        if self.config.palette != old.palette {
+            self.pending_reloads.insert(Reloadable::Palette);
+        }
+        if general.shaders != old.general.shaders {
+            self.pending_reloads.insert(Reloadable::Shaders);
+        }
+        if let Some(font) = font {
+            self.set_font(font);
+            animation.on_resize(self);
+        } else if general.relative_font_size != old.general.relative_font_size {
+            self.update_font_size();
+            animation.on_resize(self);
+        }
+
+        self.update_watched_paths();
+
+        Ok(())
+    }
+
+    fn reload_wordlist(&mut self) -> Result<(), String> {
+        self.wordlist =
+            Wordlist::from_config(&self.config.general).map_err(|err| err.to_string())?;
+
+        Ok(())
+    }
+
+    fn reload_palette(&mut self, animation: &mut dyn Animation) -> Result<(), String> {
+        self.palette = Palette::from_config(&self.config.palette).map_err(|err| err.to_string())?;
+        self.colors = self.palette.colors_for(grapheme_count(&self.word));
+        animation.on_palette_change(self);
+
+        Ok(())
+    }
+
+    fn reload_shaders(&mut self) -> Result<(), String> {
+        let shaders =
+            load_shaders(Some(&self.config.general.shaders)).map_err(|err| err.to_string())?;
+        let old = std::mem::replace(&mut self.shaders, shaders);
+        self.retired_shaders.push(old);
+
+        Ok(())
+    }
+
+    /// Pick a random word from the wordlist.
+    /// This uses the stream of the active animation.
+    pub fn random_word(&self) -> WordEntry {
         self.wordlist
-            .choose()
-            .expect("Failed to get random word")
+            .pick(self.rng.animation(), &[])
+            .expect("Wordlists always contain a word")
             .clone()
     }
+
+    /// Pick a random word, that has all of the given tags.
+    /// Returns `None`, if no word in the wordlist has these tags.
+    pub fn random_word_tagged(&self, tags: &[&str]) -> Option<WordEntry> {
+        self.wordlist.pick(self.rng.animation(), tags).cloned()
+    }
+}
+
+/// Create a render target of the given size, which is filled with black.
+fn black_render_target(width: f32, height: f32) -> RenderTarget {
+    let target = render_target(width as u32, height as u32);
+
+    push_camera_state();
+    set_camera(&render_target_camera(target, width, height));
+    clear_background(BLACK);
+    pop_camera_state();
+
+    target
 }
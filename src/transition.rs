@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use macroquad::{prelude::*, rand::RandGenerator};
+use serde::Deserialize;
+
+use crate::{helper::*, state::State};
+
+/// All available effects for the switch between two animations.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransitionKind {
+    /// Pick one of the other transitions at random.
+    Random,
+    /// Fade the previous animation to black and fade in the next one.
+    Fade,
+    /// Blend the previous animation into the next one.
+    Crossfade,
+    /// Wipe the previous animation away from left to right, revealing the next one.
+    WipeHorizontal,
+    /// Wipe the previous animation away from top to bottom, revealing the next one.
+    WipeVertical,
+    /// Reveal the next animation through a growing circle in the middle of the screen.
+    Iris,
+    /// Dissolve the previous animation into blocks of pixels.
+    Dissolve,
+    /// Break the previous animation into text cells that fly away.
+    TextShatter,
+}
+
+impl TransitionKind {
+    /// All transitions that can be picked at random.
+    pub const ALL: [TransitionKind; 7] = [
+        TransitionKind::Fade,
+        TransitionKind::Crossfade,
+        TransitionKind::WipeHorizontal,
+        TransitionKind::WipeVertical,
+        TransitionKind::Iris,
+        TransitionKind::Dissolve,
+        TransitionKind::TextShatter,
+    ];
+
+    /// Resolve `Random` into one of the actual transitions.
+    pub fn resolve(self, rng: &RandGenerator) -> TransitionKind {
+        match self {
+            TransitionKind::Random => {
+                TransitionKind::ALL[rng.gen_range(0, TransitionKind::ALL.len())]
+            }
+            kind => kind,
+        }
+    }
+}
+
+/// A single cell of the previous frame during the `TextShatter` transition.
+#[derive(Debug, Clone)]
+struct Shard {
+    /// The part of the previous frame this shard shows. Also its start position.
+    source: Rect,
+    velocity: Vec2,
+    spin: f32,
+}
+
+/// A running transition from the last frame of the previous animation to the current one.
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub timer: Duration,
+    shards: Vec<Shard>,
+}
+
+impl Transition {
+    pub fn new(state: &State, kind: TransitionKind) -> Transition {
+        let kind = kind.resolve(state.rng.state());
+        let shards = if kind == TransitionKind::TextShatter {
+            create_shards(state)
+        } else {
+            Vec::new()
+        };
+
+        Transition {
+            kind,
+            timer: Duration::from_secs(0),
+            shards,
+        }
+    }
+
+    /// How far the transition has progressed, from `0.0` to `1.0`.
+    pub fn progress(&self, duration: Duration) -> f32 {
+        if duration.is_zero() {
+            return 1.0;
+        }
+        (self.timer.as_secs_f32() / duration.as_secs_f32()).clamp(0.0, 1.0)
+    }
+
+    /// Draw the previous frame on top of the current animation.
+    pub fn draw(&self, state: &State, previous_frame: Texture2D) {
+        let progress = self.progress(state.transition_duration);
+        let width = state.window_width;
+        let height = state.window_height;
+
+        let params = DrawTextureParams {
+            flip_y: true,
+            ..Default::default()
+        };
+
+        match self.kind {
+            TransitionKind::Random => unreachable!("Random transitions are resolved on creation"),
+            TransitionKind::Fade => {
+                // Fade out to black during the first half, fade in during the second half.
+                if progress < 0.5 {
+                    draw_texture_ex(previous_frame, 0.0, 0.0, WHITE, params);
+                    draw_rectangle(
+                        0.0,
+                        0.0,
+                        width,
+                        height,
+                        Color::new(0.0, 0.0, 0.0, progress * 2.0),
+                    );
+                } else {
+                    let alpha = (1.0 - progress) * 2.0;
+                    draw_rectangle(0.0, 0.0, width, height, Color::new(0.0, 0.0, 0.0, alpha));
+                }
+            }
+            TransitionKind::Crossfade => {
+                let color = Color::new(1.0, 1.0, 1.0, 1.0 - progress);
+                draw_texture_ex(previous_frame, 0.0, 0.0, color, params);
+            }
+            TransitionKind::WipeHorizontal => {
+                let x = width * progress;
+                draw_texture_ex(
+                    previous_frame,
+                    x,
+                    0.0,
+                    WHITE,
+                    DrawTextureParams {
+                        source: Some(Rect::new(x, 0.0, width - x, height)),
+                        ..params
+                    },
+                );
+            }
+            TransitionKind::WipeVertical => {
+                // The frame is stored upside down, the bottom of the screen is at the texture's top.
+                let y = height * progress;
+                draw_texture_ex(
+                    previous_frame,
+                    0.0,
+                    y,
+                    WHITE,
+                    DrawTextureParams {
+                        source: Some(Rect::new(0.0, 0.0, width, height - y)),
+                        ..params
+                    },
+                );
+            }
+            TransitionKind::Iris | TransitionKind::Dissolve => {
+                let material = match self.kind {
+                    TransitionKind::Iris => state.shaders.iris,
+                    _ => state.shaders.dissolve,
+                };
+                material.set_uniform("Progress", progress);
+                material.set_uniform("Resolution", (width, height));
+
+                gl_use_material(material);
+                draw_texture_ex(previous_frame, 0.0, 0.0, WHITE, params);
+                gl_use_default_material();
+            }
+            TransitionKind::TextShatter => self.draw_shards(previous_frame, progress),
+        }
+    }
+
+    fn draw_shards(&self, previous_frame: Texture2D, progress: f32) {
+        let elapsed = self.timer.as_secs_f32();
+        let gravity = Vec2::new(0.0, 600.0);
+        let color = Color::new(1.0, 1.0, 1.0, 1.0 - progress);
+
+        for shard in self.shards.iter() {
+            let position = Vec2::new(shard.source.x, shard.source.y)
+                + shard.velocity * elapsed
+                + gravity * elapsed * elapsed * 0.5;
+
+            // The frame is stored upside down, which is why we have to mirror the source rect.
+            let source = Rect::new(
+                shard.source.x,
+                previous_frame.height() - shard.source.y - shard.source.h,
+                shard.source.w,
+                shard.source.h,
+            );
+
+            draw_texture_ex(
+                previous_frame,
+                position.x,
+                position.y,
+                color,
+                DrawTextureParams {
+                    source: Some(source),
+                    rotation: shard.spin * elapsed,
+                    flip_y: true,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+/// Split the screen into cells of a few characters, that explode from the screen's center.
+fn create_shards(state: &State) -> Vec<Shard> {
+    let cell_width = (state.font_dimensions.width * 4.0).max(8.0);
+    let cell_height = state.font_dimensions.height.max(8.0);
+    let center = Vec2::new(state.window_width / 2.0, state.window_height / 2.0);
+    let rng = state.rng.state();
+
+    let mut shards = Vec::new();
+    let mut y = 0.0;
+    while y < state.window_height {
+        let mut x = 0.0;
+        while x < state.window_width {
+            let source = Rect::new(x, y, cell_width, cell_height);
+
+            // Cells fly away from the center, outer cells are faster.
+            let direction = Vec2::new(x, y) - center;
+            let velocity =
+                direction * rng.gen_range(0.5, 1.5) + random_vector_with_lenght(rng, 100.0);
+
+            shards.push(Shard {
+                source,
+                velocity,
+                spin: rng.gen_range(-4.0, 4.0),
+            });
+            x += cell_width;
+        }
+        y += cell_height;
+    }
+
+    shards
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+};
+
+use log::{debug, warn};
+use notify::{recommended_watcher, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Everything that can be reloaded from disk at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Reloadable {
+    Config,
+    Wordlist,
+    Palette,
+    Shaders,
+}
+
+impl fmt::Display for Reloadable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Reloadable::Config => "config",
+            Reloadable::Wordlist => "wordlist",
+            Reloadable::Palette => "palette",
+            Reloadable::Shaders => "shaders",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A watched path, which is either a single file or a whole directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WatchedPath {
+    File(PathBuf),
+    Directory(PathBuf),
+}
+
+impl WatchedPath {
+    /// Resolve the given path, so it can be compared to the paths of file events.
+    ///
+    /// Files don't need to exist yet, but their directory does.
+    fn new(path: &Path) -> std::io::Result<WatchedPath> {
+        if path.is_dir() {
+            return Ok(WatchedPath::Directory(path.canonicalize()?));
+        }
+
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let name = path.file_name().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Path has no file name")
+        })?;
+
+        Ok(WatchedPath::File(directory.canonicalize()?.join(name)))
+    }
+
+    /// The directory that's actually watched.
+    /// Files are watched via their directory, as many editors replace files instead of writing them.
+    fn directory(&self) -> &Path {
+        match self {
+            WatchedPath::File(path) => path.parent().unwrap_or(path),
+            WatchedPath::Directory(path) => path,
+        }
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        match self {
+            WatchedPath::File(file) => file == path,
+            WatchedPath::Directory(directory) => path.starts_with(directory),
+        }
+    }
+}
+
+/// Watches the files of all [Reloadable]s and reports, which of them changed.
+///
+/// Events are collected on a background thread and picked up once per frame.
+pub struct FileWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<PathBuf>,
+    paths: HashMap<Reloadable, WatchedPath>,
+    directories: BTreeSet<PathBuf>,
+}
+
+impl FileWatcher {
+    pub fn new() -> notify::Result<FileWatcher> {
+        let (sender, events) = channel();
+        let watcher = recommended_watcher(move |event: notify::Result<notify::Event>| {
+            match event {
+                // Reading a file doesn't change it.
+                Ok(event) if matches!(event.kind, EventKind::Access(_)) => (),
+                Ok(event) => {
+                    for path in event.paths {
+                        // The receiver only goes away together with the watcher.
+                        let _ = sender.send(path);
+                    }
+                }
+                Err(err) => warn!("File watcher error: {err}"),
+            }
+        })?;
+
+        Ok(FileWatcher {
+            watcher,
+            events,
+            paths: HashMap::new(),
+            directories: BTreeSet::new(),
+        })
+    }
+
+    /// Watch the path of a reloadable. This replaces any previously watched path.
+    pub fn watch(&mut self, target: Reloadable, path: &Path) -> std::io::Result<()> {
+        self.paths.remove(&target);
+        let result = WatchedPath::new(path).map(|watched| {
+            self.paths.insert(target, watched);
+        });
+        self.update_directories();
+
+        result
+    }
+
+    /// All reloadables, whose files changed since the last call.
+    pub fn changes(&self) -> BTreeSet<Reloadable> {
+        let mut changes = BTreeSet::new();
+        for path in self.events.try_iter() {
+            for (target, watched) in self.paths.iter() {
+                if watched.matches(&path) {
+                    debug!("{path:?} changed, reloading the {target}");
+                    changes.insert(*target);
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// Only watch the directories, that are still needed.
+    fn update_directories(&mut self) {
+        let needed: BTreeSet<PathBuf> = self
+            .paths
+            .values()
+            .map(|watched| watched.directory().to_path_buf())
+            .collect();
+
+        for directory in self.directories.difference(&needed) {
+            if let Err(err) = self.watcher.unwatch(directory) {
+                warn!("Couldn't stop watching {directory:?}: {err}");
+            }
+        }
+        for directory in needed.difference(&self.directories) {
+            if let Err(err) = self.watcher.watch(directory, RecursiveMode::NonRecursive) {
+                warn!("Couldn't watch {directory:?}: {err}");
+            }
+        }
+
+        self.directories = needed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_only_match_themselves() {
+        let directory = std::env::current_dir().unwrap().canonicalize().unwrap();
+        let watched = WatchedPath::new(Path::new("wordlist.txt")).unwrap();
+
+        assert_eq!(watched, WatchedPath::File(directory.join("wordlist.txt")));
+        assert_eq!(watched.directory(), directory);
+        assert!(watched.matches(&directory.join("wordlist.txt")));
+        assert!(!watched.matches(&directory.join("wordlist.txt.swp")));
+    }
+
+    #[test]
+    fn directories_match_their_files() {
+        let directory = std::env::current_dir().unwrap().canonicalize().unwrap();
+        let watched = WatchedPath::new(Path::new("src")).unwrap();
+
+        assert_eq!(watched.directory(), directory.join("src"));
+        assert!(watched.matches(&directory.join("src").join("lib.rs")));
+        assert!(!watched.matches(&directory.join("Cargo.toml")));
+    }
+}
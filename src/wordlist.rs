@@ -0,0 +1,394 @@
+use std::{
+    fmt,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use macroquad::{prelude::Color, rand::RandGenerator};
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{
+    config::{General, DEFAULT_WORDLIST},
+    palette::parse_hex,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WordlistError {
+    #[error("Couldn't read wordlist {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The location is the path and, if known, the line of the error.
+    #[error("{location}: {message}")]
+    Parse { location: String, message: String },
+    #[error("Wordlist {path:?} doesn't contain any words")]
+    Empty { path: PathBuf },
+}
+
+/// A single word or phrase of the wordlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordEntry {
+    /// The word itself. Phrases may span several lines.
+    pub text: String,
+    /// How likely this word is picked, relative to the other words.
+    pub weight: f32,
+    /// Animations may ask for words with specific tags.
+    pub tags: Vec<String>,
+    /// Fixed colors for the graphemes of this word.
+    pub palette: Option<Vec<Color>>,
+}
+
+impl WordEntry {
+    pub fn new(text: &str) -> WordEntry {
+        WordEntry {
+            text: text.to_string(),
+            weight: default_weight(),
+            tags: Vec::new(),
+            palette: None,
+        }
+    }
+
+    /// Whether this entry has all of the given tags.
+    pub fn has_tags(&self, tags: &[&str]) -> bool {
+        tags.iter()
+            .all(|tag| self.tags.iter().any(|own| own == tag))
+    }
+}
+
+/// The words that are shown by the animations.
+///
+/// Wordlists are either plain text with one word per line,
+/// or `.toml`/`.json` files with a list of `words`. Each word is either a plain string
+/// or a table with a `text` and the optional `weight`, `tags` and `palette` keys.
+#[derive(Debug, Clone)]
+pub struct Wordlist {
+    entries: Vec<WordEntry>,
+}
+
+impl Wordlist {
+    /// A wordlist that only consists of a single word.
+    pub fn single(word: &str) -> Wordlist {
+        Wordlist {
+            entries: vec![WordEntry::new(word)],
+        }
+    }
+
+    /// Read the wordlist from the configured path.
+    /// If there's no file at the default path, the configured word is the only word.
+    /// Wordlists, that have been passed explicitly, have to exist.
+    pub fn from_config(config: &General) -> Result<Wordlist, WordlistError> {
+        if config.wordlist == Path::new(DEFAULT_WORDLIST) && !config.wordlist.exists() {
+            return Ok(Wordlist::single(&config.word));
+        }
+
+        Wordlist::read(&config.wordlist)
+    }
+
+    pub fn read(path: &Path) -> Result<Wordlist, WordlistError> {
+        let content = read_to_string(path).map_err(|source| WordlistError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Wordlist::parse(path, &content)
+    }
+
+    /// Parse a wordlist. The format is determined by the file extension.
+    pub fn parse(path: &Path, content: &str) -> Result<Wordlist, WordlistError> {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let entries = match extension {
+            Some("toml") => {
+                let file: WordlistFile = toml::from_str(content).map_err(|err| {
+                    let line = err.span().map(|span| line_of(content, span.start));
+                    parse_error(path, line, err.message())
+                })?;
+                file.words
+            }
+            Some("json") => {
+                let file: WordlistFile = serde_json::from_str(content).map_err(|err| {
+                    // The message of serde_json already contains the location, which we don't need.
+                    let message = err.to_string();
+                    let suffix = format!(" at line {} column {}", err.line(), err.column());
+                    let message = message.strip_suffix(&suffix).unwrap_or(&message);
+                    parse_error(path, Some(err.line()), message)
+                })?;
+                file.words
+            }
+            _ => content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(WordEntry::new)
+                .collect(),
+        };
+
+        if entries.is_empty() {
+            return Err(WordlistError::Empty {
+                path: path.to_path_buf(),
+            });
+        }
+
+        Ok(Wordlist { entries })
+    }
+
+    pub fn entries(&self) -> &[WordEntry] {
+        &self.entries
+    }
+
+    /// Pick a random entry, that has all of the given tags.
+    /// The chance of each entry depends on its weight.
+    pub fn pick(&self, rng: &RandGenerator, tags: &[&str]) -> Option<&WordEntry> {
+        let candidates: Vec<&WordEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.has_tags(tags))
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let total: f32 = candidates.iter().map(|entry| entry.weight).sum();
+        if total <= 0.0 {
+            return Some(candidates[rng.gen_range(0, candidates.len())]);
+        }
+
+        let mut pick = rng.gen_range(0.0, total);
+        for entry in candidates.iter() {
+            pick -= entry.weight;
+            if pick < 0.0 {
+                return Some(entry);
+            }
+        }
+        candidates.last().copied()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WordlistFile {
+    words: Vec<WordEntry>,
+}
+
+/// The table representation of a [WordEntry].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WordTable {
+    text: String,
+    #[serde(default = "default_weight")]
+    weight: f32,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    palette: Option<Vec<String>>,
+}
+
+impl<'de> Deserialize<'de> for WordEntry {
+    /// Entries are either a plain string or a table.
+    /// We don't use an untagged enum, as that would swallow the actual error messages.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = WordEntry;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a word or a table with a `text` key")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<WordEntry, E> {
+                validate_text(text)?;
+                Ok(WordEntry::new(text))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<WordEntry, A::Error> {
+                let table = WordTable::deserialize(MapAccessDeserializer::new(map))?;
+                validate_text(&table.text)?;
+                if !table.weight.is_finite() || table.weight < 0.0 {
+                    return Err(de::Error::custom(format!(
+                        "weight of '{}' must be a positive number",
+                        table.text
+                    )));
+                }
+
+                let palette = match table.palette {
+                    Some(palette) if palette.is_empty() => {
+                        return Err(de::Error::custom(format!(
+                            "palette of '{}' must not be empty",
+                            table.text
+                        )))
+                    }
+                    Some(palette) => Some(
+                        palette
+                            .iter()
+                            .map(|hex| {
+                                parse_hex(hex).ok_or_else(|| {
+                                    de::Error::custom(format!("invalid hex color '{hex}'"))
+                                })
+                            })
+                            .collect::<Result<Vec<_>, _>>()?,
+                    ),
+                    None => None,
+                };
+
+                Ok(WordEntry {
+                    text: table.text,
+                    weight: table.weight,
+                    tags: table.tags,
+                    palette,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+fn validate_text<E: de::Error>(text: &str) -> Result<(), E> {
+    if text.trim().is_empty() {
+        return Err(E::custom("words must not be empty"));
+    }
+    Ok(())
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// The (1-based) line of a byte offset.
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+fn parse_error(path: &Path, line: Option<usize>, message: &str) -> WordlistError {
+    let location = match line {
+        Some(line) => format!("{}:{line}", path.display()),
+        None => path.display().to_string(),
+    };
+
+    WordlistError::Parse {
+        location,
+        message: message.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str, content: &str) -> Result<Wordlist, WordlistError> {
+        Wordlist::parse(Path::new(name), content)
+    }
+
+    #[test]
+    fn plain_text_skips_empty_lines() {
+        let wordlist = parse("words.txt", "ROFL\n\n  LOL  \r\n\n").unwrap();
+        let texts: Vec<&str> = wordlist.entries().iter().map(|e| e.text.as_str()).collect();
+
+        assert_eq!(texts, vec!["ROFL", "LOL"]);
+    }
+
+    #[test]
+    fn toml_entries() {
+        let content = r##"
+words = [
+    "ROFL",
+    { text = "LOL", weight = 3, tags = ["short"], palette = ["#ff0000", "00ff00"] },
+    { text = """
+ROFL
+COPTER""" },
+]
+"##;
+        let wordlist = parse("words.toml", content).unwrap();
+        let entries = wordlist.entries();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0], WordEntry::new("ROFL"));
+        assert_eq!(entries[1].weight, 3.0);
+        assert_eq!(entries[1].tags, vec!["short"]);
+        assert_eq!(
+            entries[1].palette,
+            Some(vec![
+                Color::from_rgba(255, 0, 0, 255),
+                Color::from_rgba(0, 255, 0, 255)
+            ])
+        );
+        assert_eq!(entries[2].text, "ROFL\nCOPTER");
+    }
+
+    #[test]
+    fn json_entries() {
+        let content = r#"{"words": ["ROFL", {"text": "LOL", "tags": ["short"]}]}"#;
+        let wordlist = parse("words.json", content).unwrap();
+
+        assert_eq!(wordlist.entries().len(), 2);
+        assert!(wordlist.entries()[1].has_tags(&["short"]));
+    }
+
+    #[test]
+    fn errors_contain_the_line() {
+        let content =
+            "words = [\n    \"ROFL\",\n    { text = \"LOL\", palette = [\"#nothex\"] },\n]\n";
+        let err = parse("words.toml", content).unwrap_err().to_string();
+        assert!(err.starts_with("words.toml:3: "), "{err}");
+        assert!(err.contains("#nothex"), "{err}");
+
+        let content = "{\"words\": [\n  \"ROFL\",\n  {\"text\": \"LOL\", \"weight\": -1}\n]}";
+        let err = parse("words.json", content).unwrap_err().to_string();
+        assert!(err.starts_with("words.json:3: "), "{err}");
+
+        let content = "words = [\n  { text = \"LOL\", colour = 2 },\n]\n";
+        let err = parse("words.toml", content).unwrap_err().to_string();
+        assert!(err.starts_with("words.toml:2: "), "{err}");
+        assert!(err.contains("colour"), "{err}");
+    }
+
+    #[test]
+    fn empty_wordlists_are_rejected() {
+        assert!(matches!(
+            parse("words.txt", "\n  \n"),
+            Err(WordlistError::Empty { .. })
+        ));
+        assert!(parse("words.toml", "words = [\"  \"]").is_err());
+    }
+
+    #[test]
+    fn pick_respects_tags_and_weights() {
+        let content = r#"
+words = [
+    { text = "never", weight = 0 },
+    { text = "LOL", tags = ["short"] },
+    { text = "ROFLCOPTER", tags = ["long"] },
+]
+"#;
+        let wordlist = parse("words.toml", content).unwrap();
+        let rng = RandGenerator::new();
+        rng.srand(7);
+
+        for _ in 0..50 {
+            let entry = wordlist.pick(&rng, &[]).unwrap();
+            assert_ne!(entry.text, "never");
+            assert_eq!(wordlist.pick(&rng, &["short"]).unwrap().text, "LOL");
+        }
+        assert!(wordlist.pick(&rng, &["short", "long"]).is_none());
+    }
+
+    #[test]
+    fn explicit_wordlists_have_to_exist() {
+        let config = General {
+            wordlist: PathBuf::from("./does-not-exist.txt"),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            Wordlist::from_config(&config),
+            Err(WordlistError::Io { .. })
+        ));
+    }
+}